@@ -1,19 +1,636 @@
 use std::io;
 use std::process;
 
-use rust_examples::job_stat_helper::{InputStat, JobStats};
+use chrono::NaiveDate;
+use rust_examples::job_stat_helper::cohorts::{CohortPeriod, CohortTable};
+use rust_examples::job_stat_helper::events::{self, Event, EventKind};
+use rust_examples::job_stat_helper::followups::{self, FollowupConfig};
+use rust_examples::job_stat_helper::forecast::{Forecast, ForecastConfig};
+use rust_examples::job_stat_helper::history::{self, Snapshot, SnapshotDiff};
+use rust_examples::job_stat_helper::{self, InputStat, JobStats};
+use rust_examples::job_stat_helper::{
+    aliases, anonymize, business_days, calendar, channels, currency, dedupe, goals, ical,
+    interviews, metrics, offers, query, resumes, roles, scripting, tags, team,
+};
+
+const USAGE: &str = "\
+cargo run --example job_stats -- path_to_input.csv [--as-of YYYYMMDD] [--offers offers.yaml] [--rates rates.yaml|rates.csv --currency USD] [--business-days [--holidays holidays.txt|holidays.ics]] [--goals goals.yaml [--today YYYYMMDD]] [--script metrics.rhai] [--aliases aliases.yaml] [--roles roles.yaml] [--tags '#remote,level:senior'] [--search TEXT] [--where EXPR] [--by-tag hashtags|KEY]
+cargo run --example job_stats -- followups path_to_input.csv [--days N] [--interview-days N] [--format table|todo|ics] [--today YYYYMMDD]
+cargo run --example job_stats -- ics-export path_to_input.csv > interviews.ics
+cargo run --example job_stats -- ics-import path_to_input.csv calendar.ics [--apply]
+cargo run --example job_stats -- cohorts path_to_input.csv [--by week|month] [--today YYYYMMDD]
+cargo run --example job_stats -- forecast path_to_input.csv [--weeks N] [--runs N] [--seed N] [--today YYYYMMDD]
+cargo run --example job_stats -- snapshot path_to_input.csv [--history job_stats_history.yaml] [--today YYYYMMDD] [--rates rates.yaml|rates.csv --currency USD]
+cargo run --example job_stats -- diff path_to_input.csv [--history job_stats_history.yaml] [--today YYYYMMDD] [--no-save] [--rates rates.yaml|rates.csv --currency USD]
+cargo run --example job_stats -- events-export path_to_input.csv events.csv
+cargo run --example job_stats -- events-project events.csv > path_to_input.csv
+cargo run --example job_stats -- log-event events.csv APPLICATION_ID YYYYMMDD EVENT [--company C --position P --url U --referral R --channel C --resume-version V --owner O] [--round N --type phone|technical|onsite|behavioral --rating 1-5] [--amount X --currency C] [--decision accepted|declined|negotiating|expired|rescinded] [--text T]
+cargo run --example job_stats -- interviews events.csv
+cargo run --example job_stats -- anonymize path_to_input.csv --salt SALT [--shift-dates] > anonymized.csv
+cargo run --example job_stats -- team alice.csv bob.csv ...
+cargo run --example job_stats -- aliases path_to_input.csv [--aliases aliases.yaml] [--similarity 0.9]
+cargo run --example job_stats -- dedupe path_to_input.csv [--aliases aliases.yaml] [--days N]
+cargo run --example job_stats -- resumes path_to_input.csv [--window N] [--today YYYYMMDD]
+cargo run --example job_stats -- migrate-channels path_to_input.csv
+cargo run --example job_stats -- select path_to_input.csv 'company ~ \"acme\" and applied >= 2022-03-01 and not referral' > selected.csv";
+
+const DEFAULT_HISTORY: &str = "job_stats_history.yaml";
 
 fn main() {
-    // fname was passed and exists
-    let fname = match std::env::args().nth(1) {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
+        Some("followups") => run_followups(&args[1..]),
+        Some("ics-export") => run_ics_export(&args[1..]),
+        Some("ics-import") => run_ics_import(&args[1..]),
+        Some("cohorts") => run_cohorts(&args[1..]),
+        Some("forecast") => run_forecast(&args[1..]),
+        Some("snapshot") => run_snapshot(&args[1..]),
+        Some("diff") => run_diff(&args[1..]),
+        Some("events-export") => run_events_export(&args[1..]),
+        Some("events-project") => run_events_project(&args[1..]),
+        Some("log-event") => run_log_event(&args[1..]),
+        Some("interviews") => run_interviews(&args[1..]),
+        Some("anonymize") => run_anonymize(&args[1..]),
+        Some("team") => run_team(&args[1..]),
+        Some("aliases") => run_aliases(&args[1..]),
+        Some("dedupe") => run_dedupe(&args[1..]),
+        Some("resumes") => run_resumes(&args[1..]),
+        Some("migrate-channels") => run_migrate_channels(&args[1..]),
+        Some("select") => run_select(&args[1..]),
+        Some(_) => run_report(&args),
+        None => usage_and_exit(),
+    }
+}
+
+fn run_report(args: &[String]) {
+    let reporting_currency = read_reporting_currency(args);
+    let (mut input_stats, mut warnings) =
+        normalize_offers(read_input_stats(&args[0]), reporting_currency.as_ref());
+    if flag_value(args, "--aliases").is_some() {
+        input_stats = read_aliases(args).apply(&input_stats);
+    }
+    if let Some(filters) = flag_value(args, "--tags") {
+        let filters: Vec<tags::TagFilter> = filters
+            .split(',')
+            .map(|f| exit_on_error(f.parse()))
+            .collect();
+        input_stats = tags::filter_by_tags(&input_stats, &filters);
+    }
+    if let Some(query) = flag_value(args, "--search") {
+        input_stats = tags::search(&input_stats, &query);
+    }
+    if let Some(query) = flag_value(args, "--where") {
+        input_stats = query::select(&input_stats, &parse_query_or_exit(&query));
+    }
+    let mut registry = metrics::MetricRegistry::builtin();
+    tags::register_tag_metrics(&input_stats, &mut registry);
+    if let Some(script_fname) = flag_value(args, "--script") {
+        let script = exit_on_error(scripting::load_script(std::path::Path::new(&script_fname)));
+        input_stats = exit_on_error(script.filter(&input_stats));
+        script.register_metrics(&mut registry);
+    }
+    warnings.extend(job_stat_helper::validate(&input_stats));
+    for warning in warnings {
+        eprintln!("warning: {warning}");
+    }
+    let job_stats = match flag_value(args, "--as-of") {
+        Some(as_of) => JobStats::as_of_with_registry(
+            input_stats.clone(),
+            parse_date_or_exit(&as_of),
+            &registry,
+        ),
+        None => JobStats::with_registry(input_stats.clone(), &registry),
+    };
+    // a holiday calendar is only used for business days so giving one asks for them too
+    let holidays_fname = flag_value(args, "--holidays");
+    let job_stats = if args.iter().any(|a| a == "--business-days") || holidays_fname.is_some() {
+        let calendar = match holidays_fname {
+            Some(holidays_fname) => exit_on_error(business_days::load_holidays(
+                std::path::Path::new(&holidays_fname),
+            )),
+            None => business_days::HolidayCalendar::default(),
+        };
+        job_stats.with_business_days(&input_stats, &calendar)
+    } else {
+        job_stats
+    };
+
+    println!("{}", job_stats);
+
+    if let Some(goals_fname) = flag_value(args, "--goals") {
+        let goals = exit_on_error(goals::load_goals(std::path::Path::new(&goals_fname)));
+        println!(
+            "{}",
+            goals::GoalProgress::new(&input_stats, goals, today_or_flag(args))
+        );
+    }
+
+    if let Some(offers_fname) = flag_value(args, "--offers") {
+        let mut details = exit_on_error(offers::load_offer_details(std::path::Path::new(
+            &offers_fname,
+        )));
+        let mut warnings = vec![];
+        if let Some((rates, reporting)) = &reporting_currency {
+            (details, warnings) = currency::normalize_offer_details(&details, rates, reporting);
+        }
+        warnings.extend(currency::validate_offer_details(&details));
+        for warning in warnings {
+            eprintln!("warning: {warning}");
+        }
+        println!("{}", offers::OfferComparison::new(&input_stats, &details));
+    }
+
+    if let Some(group) = flag_value(args, "--by-tag") {
+        let group = exit_on_error(group.parse());
+        println!("{}", tags::TagBreakdown::new(&input_stats, group));
+    }
+
+    if let Some(roles_fname) = flag_value(args, "--roles") {
+        let rules = exit_on_error(roles::load_role_rules(std::path::Path::new(&roles_fname)));
+        println!("{}", roles::RoleBreakdown::new(&input_stats, &rules));
+    }
+}
+
+fn run_followups(args: &[String]) {
+    let fname = match args.first() {
         Some(f) => f,
-        _ => {
-            eprintln!("cargo run -- path_to_input.csv");
-            process::exit(1i32)
+        _ => usage_and_exit(),
+    };
+
+    let defaults = FollowupConfig::default();
+    let config = FollowupConfig {
+        application_days: flag_value(args, "--days")
+            .map(|d| parse_or_exit(&d, "--days"))
+            .unwrap_or(defaults.application_days),
+        interview_days: flag_value(args, "--interview-days")
+            .map(|d| parse_or_exit(&d, "--interview-days"))
+            .unwrap_or(defaults.interview_days),
+    };
+    let today = today_or_flag(args);
+
+    let input_stats = read_input_stats(fname);
+    let found = followups::followups(&input_stats, today, &config);
+
+    match flag_value(args, "--format").as_deref() {
+        None | Some("table") => print!("{}", followups::to_table(&found)),
+        Some("todo") => print!("{}", followups::to_todo_txt(&found, today)),
+        Some("ics") => print!("{}", followups::to_vtodo(&found, today)),
+        Some(other) => {
+            eprintln!("unknown format {other}, expected table, todo or ics");
+            process::exit(1i32);
+        }
+    }
+}
+
+fn run_ics_export(args: &[String]) {
+    let fname = match args.first() {
+        Some(f) => f,
+        _ => usage_and_exit(),
+    };
+
+    let input_stats = read_input_stats(fname);
+    print!(
+        "{}",
+        calendar::export_ics(&input_stats, chrono::Local::now().date_naive())
+    );
+}
+
+/// shows which first interviews would be filled in from the calendar, writing them with --apply
+fn run_ics_import(args: &[String]) {
+    let (fname, ics_fname) = match (args.first(), args.get(1)) {
+        (Some(f), Some(ics)) => (f, ics),
+        _ => usage_and_exit(),
+    };
+
+    let ics = match std::fs::read_to_string(ics_fname) {
+        Ok(ics) => ics,
+        Err(_) => {
+            eprintln!("{ics_fname} failed to open");
+            process::exit(1i32);
         }
     };
 
-    let mut csv_reader = match csv::Reader::from_path(&fname) {
+    let mut input_stats = read_input_stats(fname);
+    let updates = calendar::match_interviews(&input_stats, &ical::parse_events(&ics));
+    print!("{}", calendar::updates_diff(&updates));
+
+    if !args.iter().any(|arg| arg == "--apply") {
+        println!(
+            "{} first interviews would be filled in, rerun with --apply to write them",
+            updates.len()
+        );
+        return;
+    }
+
+    calendar::apply_interview_updates(&mut input_stats, &updates);
+    write_input_stats(fname, &input_stats);
+    println!("{} first interviews written to {fname}", updates.len());
+}
+
+fn run_cohorts(args: &[String]) {
+    let fname = match args.first() {
+        Some(f) => f,
+        _ => usage_and_exit(),
+    };
+
+    let period = match flag_value(args, "--by") {
+        Some(by) => by.parse::<CohortPeriod>().unwrap_or_else(|e| {
+            eprintln!("{e}");
+            process::exit(1i32);
+        }),
+        None => CohortPeriod::Week,
+    };
+
+    let input_stats = read_input_stats(fname);
+    print!(
+        "{}",
+        CohortTable::new(&input_stats, period, today_or_flag(args))
+    );
+}
+
+fn run_forecast(args: &[String]) {
+    let fname = match args.first() {
+        Some(f) => f,
+        _ => usage_and_exit(),
+    };
+
+    let defaults = ForecastConfig::default();
+    let config = ForecastConfig {
+        weeks: flag_value(args, "--weeks")
+            .map(|w| parse_or_exit(&w, "--weeks"))
+            .unwrap_or(defaults.weeks),
+        runs: flag_value(args, "--runs")
+            .map(|r| parse_or_exit(&r, "--runs"))
+            .unwrap_or(defaults.runs),
+        seed: flag_value(args, "--seed")
+            .map(|s| parse_or_exit(&s, "--seed"))
+            .unwrap_or(defaults.seed),
+    };
+
+    let input_stats = read_input_stats(fname);
+    print!(
+        "{}",
+        Forecast::new(&input_stats, today_or_flag(args), config)
+    );
+}
+
+fn run_snapshot(args: &[String]) {
+    let fname = match args.first() {
+        Some(f) => f,
+        _ => usage_and_exit(),
+    };
+    let history_path = flag_value(args, "--history").unwrap_or_else(|| DEFAULT_HISTORY.into());
+
+    let input_stats = read_normalized_input_stats(fname, args);
+    save_snapshot(
+        &history_path,
+        Snapshot::new(&input_stats, today_or_flag(args)),
+    );
+    println!("snapshot saved to {history_path}");
+}
+
+/// compares the input with the last saved snapshot then saves it as the new latest
+fn run_diff(args: &[String]) {
+    let fname = match args.first() {
+        Some(f) => f,
+        _ => usage_and_exit(),
+    };
+    let history_path = flag_value(args, "--history").unwrap_or_else(|| DEFAULT_HISTORY.into());
+
+    let snapshots = match history::load_history(std::path::Path::new(&history_path)) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1i32);
+        }
+    };
+
+    let input_stats = read_normalized_input_stats(fname, args);
+    let current = Snapshot::new(&input_stats, today_or_flag(args));
+    match snapshots.last() {
+        Some(previous) => print!("{}", SnapshotDiff::new(previous, &current)),
+        None => println!("no previous snapshot in {history_path} to compare with"),
+    }
+
+    if !args.iter().any(|arg| arg == "--no-save") {
+        save_snapshot(&history_path, current);
+    }
+}
+
+fn save_snapshot(history_path: &str, snapshot: Snapshot) {
+    if let Err(e) = history::append_snapshot(std::path::Path::new(history_path), snapshot) {
+        eprintln!("{e}");
+        process::exit(1i32);
+    }
+}
+
+fn run_events_export(args: &[String]) {
+    let (fname, events_fname) = match (args.first(), args.get(1)) {
+        (Some(f), Some(e)) => (f, e),
+        _ => usage_and_exit(),
+    };
+
+    let input_stats = read_input_stats(fname);
+    let written = std::fs::File::create(events_fname)
+        .map_err(events::EventLogError::from)
+        .and_then(|file| {
+            events::write_event_log(file, &events::events_from_input_stats(&input_stats))
+        });
+    exit_on_error(written);
+}
+
+fn run_events_project(args: &[String]) {
+    let events_fname = match args.first() {
+        Some(e) => e,
+        _ => usage_and_exit(),
+    };
+
+    let projected = std::fs::File::open(events_fname)
+        .map_err(events::EventLogError::from)
+        .and_then(events::read_event_log)
+        .and_then(|events| events::project(&events));
+    let input_stats = exit_on_error(projected);
+
+    let mut csv_writer = csv::Writer::from_writer(io::stdout());
+    for stat in &input_stats {
+        csv_writer.serialize(stat).unwrap();
+    }
+    csv_writer.flush().unwrap();
+}
+
+fn run_log_event(args: &[String]) {
+    let (events_fname, application_id, date, event) =
+        match (args.first(), args.get(1), args.get(2), args.get(3)) {
+            (Some(f), Some(id), Some(date), Some(event)) => (f, id, date, event),
+            _ => usage_and_exit(),
+        };
+
+    let kind = match event.as_str() {
+        "applied" => EventKind::Applied {
+            company: flag_value(args, "--company").unwrap_or_else(|| usage_and_exit()),
+            position: flag_value(args, "--position").unwrap_or_else(|| usage_and_exit()),
+            url: flag_value(args, "--url"),
+            referral: flag_value(args, "--referral"),
+            channel: flag_value(args, "--channel"),
+            resume_version: flag_value(args, "--resume-version"),
+            owner: flag_value(args, "--owner"),
+        },
+        "screened" => EventKind::Screened,
+        "interviewed" => EventKind::Interviewed {
+            round: flag_value(args, "--round")
+                .map(|r| parse_or_exit(&r, "--round"))
+                .unwrap_or(1),
+            interview_type: flag_value(args, "--type").map(|t| parse_or_exit(&t, "--type")),
+            rating: flag_value(args, "--rating").map(|r| parse_rating_or_exit(&r)),
+        },
+        "rejected" => EventKind::Rejected,
+        "offered" => EventKind::Offered {
+            amount: flag_value(args, "--amount").map(|a| parse_or_exit(&a, "--amount")),
+            currency: flag_value(args, "--currency"),
+        },
+        "declined" => EventKind::Declined,
+        "accepted" => EventKind::Accepted,
+        "decided" => EventKind::Decided {
+            decision: flag_value(args, "--decision")
+                .map(|d| parse_or_exit(&d, "--decision"))
+                .unwrap_or_else(|| usage_and_exit()),
+        },
+        "note" => EventKind::Note {
+            text: flag_value(args, "--text").unwrap_or_default(),
+        },
+        other => {
+            eprintln!("unknown event {other}");
+            process::exit(1i32);
+        }
+    };
+
+    let event = Event {
+        application_id: application_id.clone(),
+        date: parse_date_or_exit(date),
+        kind,
+    };
+    exit_on_error(events::append_events(
+        std::path::Path::new(events_fname),
+        &[event],
+    ));
+}
+
+fn run_interviews(args: &[String]) {
+    let events_fname = match args.first() {
+        Some(e) => e,
+        _ => usage_and_exit(),
+    };
+
+    let events = exit_on_error(
+        std::fs::File::open(events_fname)
+            .map_err(events::EventLogError::from)
+            .and_then(events::read_event_log),
+    );
+
+    println!("{}", interviews::InterviewStats::new(&events));
+}
+
+fn run_anonymize(args: &[String]) {
+    let fname = match args.first() {
+        Some(f) => f,
+        _ => usage_and_exit(),
+    };
+
+    let config = anonymize::AnonymizeConfig {
+        salt: flag_value(args, "--salt").unwrap_or_else(|| usage_and_exit()),
+        date_shift_days: if args.iter().any(|a| a == "--shift-dates") {
+            anonymize::random_date_shift(&mut rand::thread_rng())
+        } else {
+            0
+        },
+    };
+
+    let mut csv_writer = csv::Writer::from_writer(io::stdout());
+    for stat in anonymize::anonymize(&read_input_stats(fname), &config) {
+        csv_writer.serialize(stat).unwrap();
+    }
+    csv_writer.flush().unwrap();
+}
+
+fn run_select(args: &[String]) {
+    let (fname, query) = match args {
+        [fname, query, ..] => (fname, query),
+        _ => usage_and_exit(),
+    };
+    let expr = parse_query_or_exit(query);
+
+    let mut csv_writer = csv::Writer::from_writer(io::stdout());
+    for stat in query::select(&read_input_stats(fname), &expr) {
+        csv_writer.serialize(stat).unwrap();
+    }
+    csv_writer.flush().unwrap();
+}
+
+/// each file is one person's tracker, named after them unless its rows have an `owner` column
+fn run_team(args: &[String]) {
+    if args.is_empty() {
+        usage_and_exit();
+    }
+
+    let mut input_stats = vec![];
+    for fname in args {
+        let owner = team::owner_from_path(std::path::Path::new(fname));
+        input_stats.extend(team::with_owner(read_input_stats(fname), &owner));
+    }
+
+    print!("{}", team::TeamStats::new(input_stats));
+}
+
+fn run_aliases(args: &[String]) {
+    let fname = match args.first() {
+        Some(f) => f,
+        _ => usage_and_exit(),
+    };
+    let similarity = flag_value(args, "--similarity")
+        .map(|s| parse_or_exit(&s, "--similarity"))
+        .unwrap_or(aliases::DEFAULT_SIMILARITY);
+
+    let suggestions =
+        aliases::suggest_aliases(&read_input_stats(fname), &read_aliases(args), similarity);
+    print!("{}", aliases::to_table(&suggestions));
+}
+
+/// asks about each group of likely duplicates in turn and writes the ones confirmed back merged
+fn run_dedupe(args: &[String]) {
+    let fname = match args.first() {
+        Some(f) => f,
+        _ => usage_and_exit(),
+    };
+    let defaults = dedupe::DuplicateConfig::default();
+    let config = dedupe::DuplicateConfig {
+        window_days: flag_value(args, "--days")
+            .map(|d| parse_or_exit(&d, "--days"))
+            .unwrap_or(defaults.window_days),
+        ..defaults
+    };
+
+    // checked before asking about anything that could not be written back
+    exit_if_columns_would_be_lost(fname);
+    let input_stats = read_input_stats(fname);
+    let duplicates = dedupe::find_duplicates(&input_stats, &read_aliases(args), &config);
+    if duplicates.is_empty() {
+        println!("no likely duplicates in {fname}");
+        return;
+    }
+
+    let mut confirmed = vec![];
+    for group in dedupe::group_duplicates(&duplicates) {
+        for i in &group {
+            let stat = &input_stats[*i];
+            println!(
+                "  {}|{}|{}",
+                stat.company(),
+                stat.position(),
+                stat.applied_date()
+            );
+        }
+        println!("merge these? [y/N]");
+
+        let mut answer = String::new();
+        exit_on_error(io::stdin().read_line(&mut answer));
+        if answer.trim().eq_ignore_ascii_case("y") {
+            confirmed.push(group);
+        }
+    }
+
+    if confirmed.is_empty() {
+        return;
+    }
+    write_input_stats(fname, &dedupe::merge_groups(&input_stats, &confirmed));
+    println!(
+        "duplicates of {} applications merged in {fname}",
+        confirmed.len()
+    );
+}
+
+fn run_resumes(args: &[String]) {
+    let fname = match args.first() {
+        Some(f) => f,
+        _ => usage_and_exit(),
+    };
+    let window_days = flag_value(args, "--window")
+        .map(|w| parse_or_exit(&w, "--window"))
+        .unwrap_or(resumes::DEFAULT_WINDOW_DAYS);
+
+    print!(
+        "{}",
+        resumes::ResumeComparison::new(&read_input_stats(fname), today_or_flag(args), window_days)
+    );
+}
+
+/// writes the channel of rows recorded with only a referral into the file
+fn run_migrate_channels(args: &[String]) {
+    let fname = match args.first() {
+        Some(f) => f,
+        _ => usage_and_exit(),
+    };
+
+    let migrated = channels::migrate_referrals(&read_input_stats(fname));
+    write_input_stats(fname, &migrated);
+    println!("referrals in {fname} written as the referral channel");
+}
+
+/// the exchange rates and reporting currency given with `--rates` and `--currency`
+/// a reporting currency can not be used without rates to convert to it
+fn read_reporting_currency(args: &[String]) -> Option<(currency::ExchangeRates, String)> {
+    let rates_fname = match flag_value(args, "--rates") {
+        Some(rates_fname) => rates_fname,
+        None if flag_value(args, "--currency").is_some() => usage_and_exit(),
+        None => return None,
+    };
+    let rates = exit_on_error(currency::load_exchange_rates(std::path::Path::new(
+        &rates_fname,
+    )));
+    let reporting = flag_value(args, "--currency").unwrap_or_else(|| usage_and_exit());
+
+    Some((rates, reporting))
+}
+
+/// converts the offer amounts to the reporting currency before any offer stats are computed
+/// left as they are without one
+fn normalize_offers(
+    input_stats: Vec<InputStat>,
+    reporting_currency: Option<&(currency::ExchangeRates, String)>,
+) -> (Vec<InputStat>, Vec<job_stat_helper::ValidationWarning>) {
+    match reporting_currency {
+        Some((rates, reporting)) => currency::normalize(&input_stats, rates, reporting),
+        None => (input_stats, vec![]),
+    }
+}
+
+/// the input with its offer amounts in the currency given with `--currency`, if any
+/// warns when amounts in several currencies are left as they are
+fn read_normalized_input_stats(fname: &str, args: &[String]) -> Vec<InputStat> {
+    let (input_stats, mut warnings) = normalize_offers(
+        read_input_stats(fname),
+        read_reporting_currency(args).as_ref(),
+    );
+    warnings.extend(currency::validate(&input_stats));
+    for warning in warnings {
+        eprintln!("warning: {warning}");
+    }
+
+    input_stats
+}
+
+/// the alias file given with `--aliases`, no aliases without one
+fn read_aliases(args: &[String]) -> aliases::Aliases {
+    match flag_value(args, "--aliases") {
+        Some(aliases_fname) => {
+            exit_on_error(aliases::load_aliases(std::path::Path::new(&aliases_fname)))
+        }
+        None => aliases::Aliases::default(),
+    }
+}
+
+fn read_input_stats(fname: &str) -> Vec<InputStat> {
+    let mut csv_reader = match csv::Reader::from_path(fname) {
         Ok(rdr) => rdr,
         Err(_) => {
             eprintln!("{fname} failed to open");
@@ -21,10 +638,7 @@ fn main() {
         }
     };
 
-    let input_stats = process_job_stat_csv(&mut csv_reader);
-    let job_stats = JobStats::new(input_stats);
-
-    println!("{}", job_stats);
+    process_job_stat_csv(&mut csv_reader)
 }
 
 fn process_job_stat_csv<R: io::Read>(rdr: &mut csv::Reader<R>) -> Vec<InputStat> {
@@ -32,9 +646,121 @@ fn process_job_stat_csv<R: io::Read>(rdr: &mut csv::Reader<R>) -> Vec<InputStat>
 
     for line in rdr.deserialize() {
         job_input_stats.push(line.unwrap());
+    }
+
+    job_input_stats
+}
 
+/// replaces the file in one step so it is never left half written
+fn write_input_stats(fname: &str, input_stats: &[InputStat]) {
+    exit_if_columns_would_be_lost(fname);
 
+    let dir = match std::path::Path::new(fname).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => std::path::Path::new("."),
+    };
+    let mut csv_writer =
+        csv::Writer::from_writer(exit_on_error(tempfile::NamedTempFile::new_in(dir)));
+    for stat in input_stats {
+        exit_on_error(csv_writer.serialize(stat));
     }
+    let written = exit_on_error(csv_writer.into_inner());
+    exit_on_error(written.persist(fname));
+}
 
-    job_input_stats
+/// rewriting the file would drop any column `InputStat` does not read
+fn exit_if_columns_would_be_lost(fname: &str) {
+    let header = match csv::Reader::from_path(fname).and_then(|mut rdr| rdr.headers().cloned()) {
+        Ok(header) => header,
+        Err(_) => {
+            eprintln!("{fname} failed to open");
+            process::exit(1i32);
+        }
+    };
+
+    let unknown: Vec<&str> = header
+        .iter()
+        .filter(|column| !job_stat_helper::INPUT_COLUMNS.contains(column))
+        .collect();
+    if !unknown.is_empty() {
+        eprintln!(
+            "{fname} has columns that would be lost by rewriting it: {}",
+            unknown.join(", ")
+        );
+        process::exit(1i32);
+    }
+}
+
+/// finds the value following a flag e.g. `--days 10`
+fn flag_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+}
+
+/// the date given with --today, otherwise the actual date
+fn today_or_flag(args: &[String]) -> NaiveDate {
+    match flag_value(args, "--today") {
+        Some(d) => parse_date_or_exit(&d),
+        None => chrono::Local::now().date_naive(),
+    }
+}
+
+fn parse_or_exit<T: std::str::FromStr>(value: &str, flag: &str) -> T {
+    match value.parse() {
+        Ok(v) => v,
+        Err(_) => {
+            eprintln!("invalid value {value} for {flag}");
+            process::exit(1i32);
+        }
+    }
+}
+
+/// an interview rating from 1 (badly) to 5 (great)
+fn parse_rating_or_exit(value: &str) -> u8 {
+    match parse_or_exit(value, "--rating") {
+        rating @ 1..=5 => rating,
+        _ => {
+            eprintln!("invalid value {value} for --rating, expected 1 to 5");
+            process::exit(1i32);
+        }
+    }
+}
+
+fn parse_date_or_exit(value: &str) -> NaiveDate {
+    match NaiveDate::parse_from_str(value, "%Y%m%d") {
+        Ok(d) => d,
+        Err(_) => {
+            eprintln!("invalid date {value}, expected YYYYMMDD");
+            process::exit(1i32);
+        }
+    }
+}
+
+fn exit_on_error<T, E: std::fmt::Display>(result: Result<T, E>) -> T {
+    match result {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{e}");
+            process::exit(1i32);
+        }
+    }
+}
+
+/// shows where in the query parsing failed
+fn parse_query_or_exit(query: &str) -> query::Expr {
+    match query::parse_query(query) {
+        Ok(expr) => expr,
+        Err(e) => {
+            eprintln!("invalid query, {e}");
+            eprintln!("{}", e.pointer(query));
+            process::exit(1i32);
+        }
+    }
+}
+
+fn usage_and_exit() -> ! {
+    eprintln!("{USAGE}");
+    process::exit(1i32)
 }
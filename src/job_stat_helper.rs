@@ -1,189 +1,328 @@
 use chrono::NaiveDate;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize, Serializer};
+
+use business_days::DayCount;
+
+pub mod aliases;
+pub mod anonymize;
+pub mod business_days;
+pub mod calendar;
+pub mod channels;
+pub mod cohorts;
+pub mod currency;
+pub mod decisions;
+pub mod dedupe;
+pub mod events;
+pub mod followups;
+pub mod forecast;
+pub mod goals;
+pub mod history;
+pub mod ical;
+pub mod interviews;
+pub mod metrics;
+pub mod offers;
+pub mod query;
+pub mod referrals;
+pub mod resumes;
+pub mod roles;
+pub mod scripting;
+pub mod significance;
+pub mod tags;
+pub mod team;
+
+/// Every column of the input csv, in the order `InputStat` writes them
+pub const INPUT_COLUMNS: [&str; 16] = [
+    "company",
+    "position",
+    "applied_dt",
+    "rejected_dt",
+    "first_interview",
+    "offer_dt",
+    "offer_amt",
+    "currency",
+    "accepted",
+    "decision_dt",
+    "referral",
+    "url",
+    "notes",
+    "owner",
+    "resume_version",
+    "channel",
+];
 
 // allow dead code on clone for testing
 #[allow(dead_code)]
-#[derive(Clone, Deserialize, Debug)]
+#[derive(Clone, Deserialize, Serialize, Debug)]
 pub struct InputStat {
     company: String,
     position: String,
+    #[serde(serialize_with = "serialize_date")]
     applied_dt: f64,
+    #[serde(serialize_with = "serialize_opt_date")]
     rejected_dt: Option<f64>,
+    #[serde(serialize_with = "serialize_opt_date")]
     first_interview: Option<f64>,
+    #[serde(serialize_with = "serialize_opt_date")]
     offer_dt: Option<f64>,
     offer_amt: Option<f64>,
+    /// the currency code of `offer_amt`, e.g. `EUR`
+    #[serde(default)]
+    currency: Option<String>,
     accepted: Option<String>,
+    /// when the offer was accepted, declined, expired or rescinded
+    #[serde(default, serialize_with = "serialize_opt_date")]
+    decision_dt: Option<f64>,
     referral: Option<String>,
     url: Option<String>,
     notes: Option<String>,
+    /// whose tracker the row comes from when several people's are combined
+    #[serde(default)]
+    owner: Option<String>,
+    /// which version of my résumé and cover letter the application was sent with
+    #[serde(default)]
+    resume_version: Option<String>,
+    /// where the application came from, e.g. `job-board`, see `channels::Channel`
+    #[serde(default)]
+    channel: Option<String>,
+}
+
+impl InputStat {
+    /// the date the application was submitted
+    pub fn applied_date(&self) -> NaiveDate {
+        f64_to_datetime(self.applied_dt)
+    }
+
+    /// the date of the first interview, `None` if there was none or i declined it
+    pub fn first_interview_date(&self) -> Option<NaiveDate> {
+        self.first_interview
+            .filter(|fi| *fi != -1.0)
+            .map(f64_to_datetime)
+    }
+
+    pub fn rejected_date(&self) -> Option<NaiveDate> {
+        self.rejected_dt.map(f64_to_datetime)
+    }
+
+    pub fn offer_date(&self) -> Option<NaiveDate> {
+        self.offer_dt.map(f64_to_datetime)
+    }
+
+    pub fn decision_date(&self) -> Option<NaiveDate> {
+        self.decision_dt.map(f64_to_datetime)
+    }
+
+    /// whether i turned the first interview down, recorded as -1 instead of a date
+    pub fn declined_first_interview(&self) -> bool {
+        self.first_interview == Some(-1.0)
+    }
+
+    pub fn company(&self) -> &str {
+        &self.company
+    }
+
+    pub fn position(&self) -> &str {
+        &self.position
+    }
+
+    pub fn offer_amt(&self) -> Option<f64> {
+        self.offer_amt
+    }
+
+    pub fn currency(&self) -> Option<&str> {
+        self.currency.as_deref()
+    }
+
+    pub fn referral(&self) -> Option<&str> {
+        self.referral.as_deref()
+    }
+
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    pub fn notes(&self) -> Option<&str> {
+        self.notes.as_deref()
+    }
+
+    pub fn owner(&self) -> Option<&str> {
+        self.owner.as_deref()
+    }
+
+    pub fn resume_version(&self) -> Option<&str> {
+        self.resume_version.as_deref()
+    }
+
+    /// the `channel` column parsed, `Some(Err)` when it holds something unrecognized
+    /// rows from before the column existed came through a referral when they name one
+    pub fn channel(&self) -> Option<Result<channels::Channel, String>> {
+        match &self.channel {
+            Some(channel) => Some(channel.parse()),
+            None => self
+                .referral
+                .as_ref()
+                .map(|_| Ok(channels::Channel::Referral)),
+        }
+    }
+
+    /// the hashtags and `key:value` pairs written in the notes
+    pub fn tags(&self) -> tags::Tags {
+        tags::parse_tags(self.notes.as_deref().unwrap_or_default())
+    }
+
+    pub fn is_referral(&self) -> bool {
+        self.channel() == Some(Ok(channels::Channel::Referral))
+    }
+
+    /// the `accepted` column parsed, `Some(Err)` when it holds something unrecognized
+    pub fn offer_decision(&self) -> Option<Result<decisions::OfferDecision, String>> {
+        self.accepted.as_deref().map(str::parse)
+    }
+
+    /// The record as it looked on `date`, `None` if it had not been applied to yet
+    /// anything dated after `date` is dropped. An interview i declined has no date so it is kept
+    fn as_of(&self, date: NaiveDate) -> Option<InputStat> {
+        if self.applied_date() > date {
+            return None;
+        }
+
+        let mut stat = self.clone();
+        if stat.rejected_date().map_or(false, |d| d > date) {
+            stat.rejected_dt = None;
+        }
+        if stat.first_interview_date().map_or(false, |d| d > date) {
+            stat.first_interview = None;
+        }
+        if stat.offer_date().map_or(false, |d| d > date) {
+            stat.offer_dt = None;
+            stat.offer_amt = None;
+            stat.currency = None;
+            stat.accepted = None;
+            stat.decision_dt = None;
+        }
+        if stat.decision_date().map_or(false, |d| d > date) {
+            stat.accepted = None;
+            stat.decision_dt = None;
+        }
+
+        Some(stat)
+    }
+
+    /// A stable identifier for the application built from the company, position and applied date
+    /// e.g. `acme-backend-engineer-20220401`
+    pub fn application_id(&self) -> String {
+        let slug = format!("{} {}", self.company, self.position)
+            .to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<&str>>()
+            .join("-");
+
+        format!("{}-{}", slug, self.applied_date().format("%Y%m%d"))
+    }
+}
+
+/// Reconstructs the input stats as they were on `date`
+pub fn input_stats_as_of(input_stats: &[InputStat], date: NaiveDate) -> Vec<InputStat> {
+    input_stats
+        .iter()
+        .filter_map(|stat| stat.as_of(date))
+        .collect()
+}
+
+/// Something in an input row that looks wrong but does not stop the stats from being computed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationWarning {
+    pub application_id: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ValidationWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "{}: {}", self.application_id, self.message)
+    }
+}
+
+/// Checks every row for values that are ignored or contradict each other
+pub fn validate(input_stats: &[InputStat]) -> Vec<ValidationWarning> {
+    input_stats
+        .iter()
+        .flat_map(decisions::validate)
+        .chain(input_stats.iter().flat_map(channels::validate))
+        .chain(currency::validate(input_stats))
+        .collect()
 }
 
 /// converts a datetime stored as a float to a datetime
 fn f64_to_datetime(date: f64) -> NaiveDate {
     // unwrap is okay in this case because the format forces no decimal
-    NaiveDate::parse_from_str(&format!("{}", date), "%Y%m%d").unwrap()
+    try_f64_to_datetime(date).unwrap()
+}
+
+/// converts a datetime stored as a float, `None` if it is not a valid YYYYMMDD date
+fn try_f64_to_datetime(date: f64) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(&format!("{}", date), "%Y%m%d").ok()
+}
+
+/// converts a datetime to the float format used in the input csv
+fn datetime_to_f64(date: NaiveDate) -> f64 {
+    // unwrap is okay because a formatted date is always a valid number
+    date.format("%Y%m%d").to_string().parse().unwrap()
+}
+
+/// writes a float date the way it was read e.g. `20220408` rather than `20220408.0`
+fn serialize_date<S: Serializer>(date: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_i64(*date as i64)
+}
+
+fn serialize_opt_date<S: Serializer>(date: &Option<f64>, serializer: S) -> Result<S::Ok, S::Error> {
+    match date {
+        Some(d) => serializer.serialize_some(&(*d as i64)),
+        None => serializer.serialize_none(),
+    }
 }
 
 pub struct JobStats {
-    num_applied: u8,
-    num_offers: u8,
-    num_rejections_all_types: u8,
-    num_rejections_no_first_interview: u8,
-    num_first_interviews_i_declined: u8,
-    num_no_response_following_application: u8,
-    num_first_interviews_taken: u8,
-    num_rejection_after_first_interview: u8,
-    num_referrals: u8,
-    mean_days_between_application_first_interview: f64,
-    median_days_between_application_and_first_interview: f64,
-    shortest_days_between_application_and_first_interview: i64,
-    longest_days_between_application_and_first_interview: i64,
-    mean_days_between_application_and_rejection: f64,
-    median_days_between_application_and_rejection: f64,
-    shortest_days_between_application_and_rejection: i64,
-    longest_days_between_application_and_rejection: i64,
-    mean_time_between_first_interview_and_offer: f64,
-    median_time_between_first_interview_and_offer: f64,
-    shortest_time_between_first_interview_and_offer: i64,
-    longest_time_between_first_interview_and_offer: i64,
-    mean_time_between_first_interview_and_rejection: f64,
-    median_time_between_first_interview_and_rejection: f64,
-    longest_time_betwen_first_interview_and_rejection: i64,
-    shortest_time_betwen_first_interview_and_rejection: i64,
+    /// the date the stats were reconstructed for, `None` when computed over everything
+    as_of: Option<NaiveDate>,
+    metrics: Vec<metrics::MetricResult>,
+    /// whether any reported rate is based on too few applications to mean much
+    small_sample: bool,
+    referral_effectiveness: referrals::ReferralEffectiveness,
+    channels: channels::ChannelStats,
+    offer_decisions: decisions::DecisionStats,
+    /// the durations again in business days, only when asked for
+    business_days: Option<business_days::DurationComparison>,
 }
 
 impl JobStats {
     pub fn new(raw_input: Vec<InputStat>) -> Self {
-        let num_first_interviews_taken = raw_input
-            .clone()
-            .into_iter()
-            .filter(|ri| ri.first_interview.is_some())
-            .collect::<Vec<InputStat>>()
-            .len() as u8;
+        Self::with_registry(raw_input, &metrics::MetricRegistry::builtin())
+    }
+
+    /// Computes the metrics of `registry` rather than the built in ones
+    pub fn with_registry(raw_input: Vec<InputStat>, registry: &metrics::MetricRegistry) -> Self {
+        let num_rejections = raw_input.iter().filter(|s| s.rejected_dt.is_some()).count();
+        let referral_effectiveness = referrals::ReferralEffectiveness::new(&raw_input);
 
         Self {
-            num_applied: raw_input.len() as u8,
-            num_offers: raw_input
-                .clone()
-                .into_iter()
-                .filter(|ri| ri.offer_dt.is_some())
-                .collect::<Vec<InputStat>>()
-                .len() as u8,
-            num_rejections_all_types: raw_input
-                .clone()
+            as_of: None,
+            metrics: registry.evaluate(&raw_input),
+            small_sample: [raw_input.len(), num_rejections]
                 .into_iter()
-                .filter(|ri| ri.rejected_dt.is_some())
-                .collect::<Vec<InputStat>>()
-                .len() as u8,
-            num_rejections_no_first_interview: raw_input
-                .clone()
-                .into_iter()
-                .filter(|ri| ri.rejected_dt.is_some() && ri.first_interview.is_none())
-                .collect::<Vec<InputStat>>()
-                .len() as u8,
-            num_first_interviews_i_declined: raw_input
-                .clone()
-                .into_iter()
-                .filter(|ri| ri.first_interview.unwrap_or(0.0f64) == -1.00)
-                .collect::<Vec<InputStat>>()
-                .len() as u8,
-            num_no_response_following_application: raw_input
-                .clone()
-                .into_iter()
-                .filter(|ri| ri.rejected_dt.is_none() && ri.first_interview.is_none())
-                .collect::<Vec<InputStat>>()
-                .len() as u8,
-            num_first_interviews_taken,
-            num_rejection_after_first_interview: raw_input
-                .clone()
-                .into_iter()
-                .filter(|ri| ri.first_interview.is_some() && ri.rejected_dt.is_some())
-                .collect::<Vec<InputStat>>()
-                .len() as u8,
-            num_referrals: raw_input
-                .clone()
-                .into_iter()
-                .filter(|ri| ri.referral.is_some())
-                .collect::<Vec<InputStat>>()
-                .len() as u8,
-            mean_days_between_application_first_interview: {
-                mean(&days_between_application_and_first_interview(&raw_input)).unwrap()
-            },
-            mean_days_between_application_and_rejection: mean(
-                &days_between_application_and_rejection(&raw_input),
-            )
-            .unwrap(),
-            mean_time_between_first_interview_and_offer: mean(
-                &days_between_first_interview_and_offer(&raw_input),
-            )
-            .unwrap(),
-            median_days_between_application_and_first_interview: median(
-                &mut days_between_application_and_first_interview(&raw_input),
-            )
-            .unwrap(),
-            shortest_days_between_application_and_first_interview:
-                days_between_application_and_first_interview(&raw_input)
-                    .into_iter()
-                    .min()
-                    .unwrap(),
-            longest_days_between_application_and_first_interview:
-                days_between_application_and_first_interview(&raw_input)
-                    .into_iter()
-                    .max()
-                    .unwrap(),
-            median_days_between_application_and_rejection: median(
-                &mut days_between_application_and_rejection(&raw_input),
-            )
-            .unwrap(),
-            shortest_days_between_application_and_rejection:
-                days_between_application_and_rejection(&raw_input)
-                    .into_iter()
-                    .min()
-                    .unwrap(),
-            longest_days_between_application_and_rejection: days_between_application_and_rejection(
-                &raw_input,
-            )
-            .into_iter()
-            .max()
-            .unwrap(),
-            median_time_between_first_interview_and_offer: median(
-                &mut days_between_first_interview_and_offer(&raw_input),
-            )
-            .unwrap(),
-            shortest_time_between_first_interview_and_offer:
-                days_between_first_interview_and_offer(&raw_input)
-                    .into_iter()
-                    .min()
-                    .unwrap(),
-            longest_time_between_first_interview_and_offer: days_between_first_interview_and_offer(
-                &raw_input,
-            )
-            .into_iter()
-            .max()
-            .unwrap(),
-            mean_time_between_first_interview_and_rejection: mean(
-                &days_between_first_interview_and_rejection(&raw_input),
-            )
-            .unwrap(),
-            median_time_between_first_interview_and_rejection: median(
-                &mut days_between_first_interview_and_rejection(&raw_input),
-            )
-            .unwrap(),
-            shortest_time_betwen_first_interview_and_rejection:
-                days_between_first_interview_and_rejection(&raw_input)
-                    .into_iter()
-                    .min()
-                    .unwrap(),
-            longest_time_betwen_first_interview_and_rejection:
-                days_between_first_interview_and_rejection(&raw_input)
-                    .into_iter()
-                    .max()
-                    .unwrap(),
+                .any(|whole| (whole as u32) < significance::SMALL_SAMPLE_SIZE)
+                || referral_effectiveness.has_small_sample(),
+            referral_effectiveness,
+            channels: channels::ChannelStats::new(&raw_input),
+            offer_decisions: decisions::DecisionStats::new(&raw_input),
+            business_days: None,
         }
     }
 }
 
-fn days_between_first_interview_and_rejection(input_stats: &Vec<InputStat>) -> Vec<i64> {
+fn days_between_first_interview_and_rejection(
+    input_stats: &[InputStat],
+    days: DayCount,
+) -> Vec<i64> {
     let mut days_between_first_interview_and_offer = vec![];
 
     for stat in input_stats {
@@ -191,18 +330,17 @@ fn days_between_first_interview_and_rejection(input_stats: &Vec<InputStat>) -> V
             && stat.first_interview.is_some()
             && stat.first_interview.unwrap() != -1.0
         {
-            days_between_first_interview_and_offer.push(
-                (f64_to_datetime(stat.rejected_dt.unwrap())
-                    - f64_to_datetime(stat.first_interview.unwrap()))
-                .num_days(),
-            );
+            days_between_first_interview_and_offer.push(days.between(
+                f64_to_datetime(stat.first_interview.unwrap()),
+                f64_to_datetime(stat.rejected_dt.unwrap()),
+            ));
         }
     }
 
     days_between_first_interview_and_offer
 }
 
-fn days_between_first_interview_and_offer(input_stats: &Vec<InputStat>) -> Vec<i64> {
+fn days_between_first_interview_and_offer(input_stats: &[InputStat], days: DayCount) -> Vec<i64> {
     let mut days_between_first_interview_and_offer = vec![];
 
     for stat in input_stats {
@@ -210,18 +348,17 @@ fn days_between_first_interview_and_offer(input_stats: &Vec<InputStat>) -> Vec<i
             && stat.first_interview.is_some()
             && stat.first_interview.unwrap() != -1.0
         {
-            days_between_first_interview_and_offer.push(
-                (f64_to_datetime(stat.offer_dt.unwrap())
-                    - f64_to_datetime(stat.first_interview.unwrap()))
-                .num_days(),
-            );
+            days_between_first_interview_and_offer.push(days.between(
+                f64_to_datetime(stat.first_interview.unwrap()),
+                f64_to_datetime(stat.offer_dt.unwrap()),
+            ));
         }
     }
 
     days_between_first_interview_and_offer
 }
 
-fn days_between_application_and_rejection(input_stats: &Vec<InputStat>) -> Vec<i64> {
+fn days_between_application_and_rejection(input_stats: &[InputStat], days: DayCount) -> Vec<i64> {
     let mut days_between_application_and_rejection = vec![];
 
     for stat in input_stats {
@@ -229,17 +366,20 @@ fn days_between_application_and_rejection(input_stats: &Vec<InputStat>) -> Vec<i
             && stat.first_interview.is_some()
             && stat.first_interview.unwrap() != -1.0
         {
-            days_between_application_and_rejection.push(
-                (f64_to_datetime(stat.rejected_dt.unwrap()) - f64_to_datetime(stat.applied_dt))
-                    .num_days(),
-            );
+            days_between_application_and_rejection.push(days.between(
+                f64_to_datetime(stat.applied_dt),
+                f64_to_datetime(stat.rejected_dt.unwrap()),
+            ));
         }
     }
 
     days_between_application_and_rejection
 }
 
-fn days_between_application_and_first_interview(input_stats: &Vec<InputStat>) -> Vec<i64> {
+fn days_between_application_and_first_interview(
+    input_stats: &[InputStat],
+    days: DayCount,
+) -> Vec<i64> {
     let mut days_between_application_and_first_interview = vec![];
 
     for stat in input_stats {
@@ -247,156 +387,150 @@ fn days_between_application_and_first_interview(input_stats: &Vec<InputStat>) ->
             && stat.first_interview.is_some()
             && stat.first_interview.unwrap() != -1.0
         {
-            days_between_application_and_first_interview.push(
-                (f64_to_datetime(stat.first_interview.unwrap()) - f64_to_datetime(stat.applied_dt))
-                    .num_days(),
-            );
+            days_between_application_and_first_interview.push(days.between(
+                f64_to_datetime(stat.applied_dt),
+                f64_to_datetime(stat.first_interview.unwrap()),
+            ));
         }
     }
 
     days_between_application_and_first_interview
 }
 
+impl JobStats {
+    /// Computes the stats as they looked on `date`, ignoring applications submitted and
+    /// rejections, interviews or offers that happened after it
+    pub fn as_of(raw_input: Vec<InputStat>, date: NaiveDate) -> Self {
+        Self::as_of_with_registry(raw_input, date, &metrics::MetricRegistry::builtin())
+    }
+
+    pub fn as_of_with_registry(
+        raw_input: Vec<InputStat>,
+        date: NaiveDate,
+        registry: &metrics::MetricRegistry,
+    ) -> Self {
+        Self {
+            as_of: Some(date),
+            ..Self::with_registry(input_stats_as_of(&raw_input, date), registry)
+        }
+    }
+
+    /// Adds a table of every duration in both calendar and business days
+    /// `raw_input` is cut off at the as of date when the stats have one
+    pub fn with_business_days(
+        self,
+        raw_input: &[InputStat],
+        calendar: &business_days::HolidayCalendar,
+    ) -> Self {
+        let input_stats = match self.as_of {
+            Some(date) => input_stats_as_of(raw_input, date),
+            None => raw_input.to_vec(),
+        };
+        let durations = metrics::builtin_days_between()
+            .into_iter()
+            .map(|(name, days_between)| {
+                (
+                    name,
+                    days_between(&input_stats, DayCount::Calendar),
+                    days_between(&input_stats, DayCount::Business(calendar)),
+                )
+            })
+            .collect();
+
+        Self {
+            business_days: Some(business_days::DurationComparison::new(durations)),
+            ..self
+        }
+    }
+
+    /// The value of the metric with this name, `None` if it could not be computed or there is
+    /// no such metric
+    pub fn value(&self, name: &str) -> Option<f64> {
+        self.metrics
+            .iter()
+            .find(|m| m.name == name)
+            .and_then(|m| m.value)
+    }
+
+    pub fn metrics(&self) -> &[metrics::MetricResult] {
+        &self.metrics
+    }
+
+    /// Every metric and the offer decision counts by name, used to compare runs over time
+    pub fn summary_metrics(&self) -> Vec<(String, Option<f64>)> {
+        self.metrics
+            .iter()
+            .map(|m| (m.name.clone(), m.value))
+            .chain([
+                (
+                    String::from("Offers accepted"),
+                    Some(self.offer_decisions.accepted.into()),
+                ),
+                (
+                    String::from("Offers pending decision"),
+                    Some(self.offer_decisions.pending.len() as f64),
+                ),
+                (
+                    String::from("Mean time (days) between offer and decision"),
+                    self.offer_decisions.mean_days_offer_to_decision,
+                ),
+            ])
+            .collect()
+    }
+}
+
 /// Divides 2 floats and outputs the result as a string with a precision of 2
 fn percent_with_precision_2(part: f64, whole: f64) -> String {
     format!("{:.2}%", (part / whole) * 100.0f64)
 }
 
 impl std::fmt::Display for JobStats {
-    fn fmt(&self, _: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
-        println!("|Metric|Value|");
-        println!("|--------|-------|");
-        println!("|Jobs applied to|{}|", self.num_applied);
-        println!(
-            "|Count of referrals | {} ({} of all jobs applied to) |",
-            self.num_referrals,
-            percent_with_precision_2(self.num_referrals.into(), self.num_applied.into())
-        );
-        println!(
-            "|First interviews completed| {} ({} of jobs applied to)|",
-            self.num_first_interviews_taken,
-            percent_with_precision_2(
-                self.num_first_interviews_taken.into(),
-                self.num_applied.into()
-            )
-        );
-        println!(
-            "|First interviews I declined | {} ({} of all jobs applied to)|",
-            self.num_first_interviews_i_declined,
-            percent_with_precision_2(
-                self.num_first_interviews_i_declined.into(),
-                self.num_applied.into()
-            )
-        );
-        println!(
-            "|Offers |{} ({} of al jobs applied to)|",
-            self.num_offers,
-            percent_with_precision_2(self.num_offers.into(), self.num_applied.into())
-        );
-        println!(
-            "|Rejections (all types)|{} ({} of all jobs applied to)|",
-            self.num_rejections_all_types,
-            percent_with_precision_2(
-                self.num_rejections_all_types.into(),
-                self.num_applied.into()
-            )
-        );
-        println!(
-            "|Rejections without first interview|{} ({} of rejections (all types))|",
-            self.num_rejections_no_first_interview,
-            percent_with_precision_2(
-                self.num_rejections_no_first_interview.into(),
-                self.num_rejections_all_types.into()
-            )
-        );
-        println!(
-            "|Rejection after first interview | {} ({} of rejections (all types)) |",
-            self.num_rejection_after_first_interview,
-            percent_with_precision_2(
-                self.num_rejection_after_first_interview.into(),
-                self.num_rejections_all_types.into()
-            )
-        );
-        println!(
-            "|No Response following application | {} ({} of rejections (all types))|",
-            self.num_no_response_following_application,
-            percent_with_precision_2(
-                self.num_no_response_following_application.into(),
-                self.num_rejections_all_types.into()
-            )
-        );
-        println!("|----|---|");
-        println!(
-            "|Mean time (days) between application date and first interview|{}|",
-            self.mean_days_between_application_first_interview
-        );
-        println!(
-            "|Median time (days) between application date and first interview|{}|",
-            self.median_days_between_application_and_first_interview
-        );
-        println!(
-            "|Shortest time (days) between application date and first interview|{}|",
-            self.shortest_days_between_application_and_first_interview
-        );
-        println!(
-            "|Longest time (days) between application date and first interview|{}|",
-            self.longest_days_between_application_and_first_interview
-        );
-        println!("|---|---|");
-        println!(
-            "|Mean time (days) between application and rejection|{}|",
-            self.mean_days_between_application_and_rejection
-        );
-        println!(
-            "|Median time (days) between application and rejection|{}|",
-            self.median_days_between_application_and_rejection
-        );
-        println!(
-            "|Shortest time (days) between application and rejection|{}|",
-            self.shortest_days_between_application_and_rejection
-        );
-        println!(
-            "|Longest time (days) between application and rejection|{}|",
-            self.longest_days_between_application_and_rejection
-        );
-        println!("|---|---|");
-        println!(
-            "|Mean time (days) between first interview and rejection|{}|",
-            self.mean_time_between_first_interview_and_rejection
-        );
-        println!(
-            "|Median time (days) between first interview and rejection|{}|",
-            self.median_time_between_first_interview_and_rejection
-        );
-        println!(
-            "|Shortest time (days) between first interview and rejection |{}|",
-            self.shortest_time_betwen_first_interview_and_rejection
-        );
-        println!(
-            "|Longest time (days) between first interview and rejection|{}|",
-            self.longest_time_betwen_first_interview_and_rejection
-        );
-        println!("|---|---|");
-        println!(
-            "|Mean time (days) between first interview and offer|{}|",
-            self.mean_time_between_first_interview_and_offer
-        );
-        println!(
-            "|Median time (days) between first interview and offer|{}|",
-            self.median_time_between_first_interview_and_offer
-        );
-        println!(
-            "|Shortest time (days) between first interview and offer|{}|",
-            self.shortest_time_between_first_interview_and_offer
-        );
-        println!(
-            "|Longest time (days) between first interview and offer|{}|",
-            self.longest_time_between_first_interview_and_offer
-        );
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        writeln!(f, "|Metric|Value|")?;
+        writeln!(f, "|--------|-------|")?;
+        if let Some(as_of) = self.as_of {
+            writeln!(f, "|As of|{}|", as_of)?;
+        }
+        let mut breaks = 0;
+        for (i, metric) in self.metrics.iter().enumerate() {
+            if i > 0 && metric.section != self.metrics[i - 1].section {
+                // the break after the counts has always been drawn wider than the rest
+                let separator = if breaks == 0 {
+                    "|----|---|"
+                } else {
+                    "|---|---|"
+                };
+                writeln!(f, "{}", separator)?;
+                breaks += 1;
+            }
+            writeln!(f, "|{}|{}|", metric.name, metric.formatted)?;
+        }
+        if let Some(business_days) = &self.business_days {
+            writeln!(f)?;
+            write!(f, "{}", business_days)?;
+        }
+        writeln!(f)?;
+        write!(f, "{}", self.referral_effectiveness)?;
+        writeln!(f)?;
+        write!(f, "{}", self.channels)?;
+        writeln!(f)?;
+        write!(f, "{}", self.offer_decisions)?;
+        if self.small_sample {
+            writeln!(f)?;
+            writeln!(f, "{}", significance::small_sample_warning())?;
+        }
         Ok(())
     }
 }
 
+/// shows a metric that can not be computed without enough data as n/a
+fn display_or_na<T: std::fmt::Display>(value: Option<T>) -> String {
+    match value {
+        Some(v) => v.to_string(),
+        None => String::from("n/a"),
+    }
+}
+
 /// Finds the mean of a vector of numbers
 fn mean(v: &[i64]) -> Option<f64> {
     if v.len() > 0 {
@@ -427,6 +561,101 @@ fn median(v: &mut [i64]) -> Option<f64> {
     Some(v[(s / 2) as usize] as f64)
 }
 
+/// parses csv text into input stats for use in tests
+#[cfg(test)]
+pub(crate) fn input_stats_from_csv(csv_text: &str) -> Vec<InputStat> {
+    csv::Reader::from_reader(csv_text.as_bytes())
+        .deserialize()
+        .map(|line| line.unwrap())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_job_stats_as_of() {
+        let input_stats = input_stats_from_csv(
+            "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes
+A,Engineer,20220201,,20220215,20220310,100000,yes,,,
+B,Engineer,20220210,20220305,20220220,,,,,,
+C,Engineer,20220220,,-1,,,,,,
+D,Engineer,20220305,,,,,,,,
+",
+        );
+        let march_1 = NaiveDate::from_ymd_opt(2022, 3, 1).unwrap();
+
+        let as_of = input_stats_as_of(&input_stats, march_1);
+        assert_eq!(3, as_of.len());
+        assert_eq!(None, as_of[0].offer_dt);
+        assert_eq!(None, as_of[0].offer_amt);
+        assert_eq!(None, as_of[1].rejected_dt);
+        assert_eq!(Some(-1.0), as_of[2].first_interview);
+
+        let job_stats = JobStats::as_of(input_stats, march_1);
+        assert_eq!(Some(3.0), job_stats.value("Jobs applied to"));
+        assert_eq!(Some(0.0), job_stats.value("Offers"));
+        assert_eq!(Some(0.0), job_stats.value("Rejections (all types)"));
+        assert_eq!(Some(3.0), job_stats.value("First interviews completed"));
+        assert_eq!(
+            None,
+            job_stats.value("Mean time (days) between first interview and offer")
+        );
+        assert!(job_stats.to_string().contains("|As of|2022-03-01|"));
+    }
+
+    #[test]
+    fn test_input_columns() {
+        let input_stats = input_stats_from_csv(
+            "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes
+A,Engineer,20220201,,,,,,,,
+",
+        );
+        let mut csv_writer = csv::Writer::from_writer(vec![]);
+        csv_writer.serialize(&input_stats[0]).unwrap();
+        let written = csv_writer.into_inner().unwrap();
+
+        let header = csv::Reader::from_reader(written.as_slice())
+            .headers()
+            .unwrap()
+            .clone();
+        assert_eq!(INPUT_COLUMNS.to_vec(), header.iter().collect::<Vec<&str>>());
+    }
+
+    #[test]
+    fn test_job_stats_with_business_days() {
+        let input_stats = input_stats_from_csv(
+            "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes
+A,Engineer,20220520,20220523,,,,,,,
+B,Engineer,20220527,20220531,20220527,,,,,,
+",
+        );
+        let calendar = business_days::HolidayCalendar {
+            holidays: [NaiveDate::from_ymd_opt(2022, 5, 30).unwrap()].into(),
+        };
+
+        assert!(!JobStats::new(input_stats.clone())
+            .to_string()
+            .contains("Business days"));
+        // a rejection only counts towards application and rejection after an interview
+        let rendered = JobStats::new(input_stats.clone())
+            .with_business_days(&input_stats, &calendar)
+            .to_string();
+        assert!(rendered.contains("|Mean time between application and rejection|4|1|"));
+        assert!(rendered.contains("|Longest time between first interview and rejection|4|1|"));
+
+        // only weekends are skipped without a holiday calendar
+        let rendered = JobStats::new(input_stats.clone())
+            .with_business_days(&input_stats, &business_days::HolidayCalendar::default())
+            .to_string();
+        assert!(rendered.contains("|Mean time between application and rejection|4|2|"));
+    }
+}
+
 //#[cfg(test)]
 //mod test {
     //use super::*;
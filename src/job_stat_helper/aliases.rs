@@ -0,0 +1,242 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::InputStat;
+
+/// How similar two company names have to be, by Jaro-Winkler, to be suggested as the same
+pub const DEFAULT_SIMILARITY: f64 = 0.9;
+
+/// Words at the end of a company name that do not tell companies apart
+const LEGAL_SUFFIXES: [&str; 13] = [
+    "inc",
+    "incorporated",
+    "corp",
+    "corporation",
+    "co",
+    "company",
+    "llc",
+    "ltd",
+    "limited",
+    "gmbh",
+    "plc",
+    "ag",
+    "sa",
+];
+
+#[derive(Debug, thiserror::Error)]
+pub enum AliasesError {
+    #[error("failed to read the alias file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the alias file is not valid yaml: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+}
+
+/// The name each company and position is reported under, with the other ways it is written
+/// ```yaml
+/// companies:
+///   Acme: [ACME Inc., acme corp]
+/// positions:
+///   Software Engineer: [SWE, Software Dev]
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Aliases {
+    #[serde(default)]
+    pub companies: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub positions: BTreeMap<String, Vec<String>>,
+}
+
+pub fn load_aliases(path: &Path) -> Result<Aliases, AliasesError> {
+    Ok(serde_yaml::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+fn words(name: &str) -> Vec<String> {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// The company name as compared, lowercased words without punctuation or a trailing legal
+/// suffix so `ACME Inc.` and `acme` are the same
+pub fn company_key(company: &str) -> String {
+    let mut words = words(company);
+    while words.len() > 1 && LEGAL_SUFFIXES.contains(&words[words.len() - 1].as_str()) {
+        words.pop();
+    }
+    words.join(" ")
+}
+
+/// The position as compared, lowercased words without punctuation
+pub fn position_key(position: &str) -> String {
+    words(position).join(" ")
+}
+
+/// the canonical name whose own key or one of whose aliases' keys is `key`
+fn canonical(names: &BTreeMap<String, Vec<String>>, name: &str, key: fn(&str) -> String) -> String {
+    let wanted = key(name);
+    names
+        .iter()
+        .find(|(canonical, aliases)| {
+            key(canonical) == wanted || aliases.iter().any(|a| key(a) == wanted)
+        })
+        .map_or_else(
+            || name.trim().to_string(),
+            |(canonical, _)| canonical.clone(),
+        )
+}
+
+impl Aliases {
+    /// The name the company is reported under, itself trimmed when it has no alias
+    pub fn company(&self, company: &str) -> String {
+        canonical(&self.companies, company, company_key)
+    }
+
+    /// The name the position is reported under, itself trimmed when it has no alias
+    pub fn position(&self, position: &str) -> String {
+        canonical(&self.positions, position, position_key)
+    }
+
+    /// Every company and position replaced by the name it is reported under
+    pub fn apply(&self, input_stats: &[InputStat]) -> Vec<InputStat> {
+        input_stats
+            .iter()
+            .map(|stat| InputStat {
+                company: self.company(&stat.company),
+                position: self.position(&stat.position),
+                ..stat.clone()
+            })
+            .collect()
+    }
+}
+
+/// Two spellings that are probably the same company, `alias` is the less used one
+#[derive(Clone, Debug, PartialEq)]
+pub struct AliasSuggestion {
+    pub canonical: String,
+    pub alias: String,
+    /// 1 when they only differ in case, punctuation or legal suffix
+    pub similarity: f64,
+}
+
+/// Companies that are still written more than one way once `aliases` are applied, most similar
+/// first. Each spelling is suggested as an alias of the more used one, or the shorter one when
+/// they are used as often
+pub fn suggest_aliases(
+    input_stats: &[InputStat],
+    aliases: &Aliases,
+    threshold: f64,
+) -> Vec<AliasSuggestion> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for stat in aliases.apply(input_stats) {
+        *counts.entry(stat.company).or_default() += 1;
+    }
+    let companies: Vec<(&String, &usize)> = counts.iter().collect();
+
+    let mut suggestions = vec![];
+    for (i, (a, a_count)) in companies.iter().enumerate() {
+        for (b, b_count) in &companies[i + 1..] {
+            let similarity = strsim::jaro_winkler(&company_key(a), &company_key(b));
+            if similarity < threshold {
+                continue;
+            }
+            let (canonical, alias) =
+                if (b_count, std::cmp::Reverse(b.len())) > (a_count, std::cmp::Reverse(a.len())) {
+                    (b, a)
+                } else {
+                    (a, b)
+                };
+            suggestions.push(AliasSuggestion {
+                canonical: canonical.to_string(),
+                alias: alias.to_string(),
+                similarity,
+            });
+        }
+    }
+    suggestions.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+
+    suggestions
+}
+
+/// Renders suggestions as a markdown table
+pub fn to_table(suggestions: &[AliasSuggestion]) -> String {
+    let mut out = String::from("|Company|Probably the same as|Similarity|\n");
+    out.push_str("|---|---|---|\n");
+    for suggestion in suggestions {
+        out.push_str(&format!(
+            "|{}|{}|{:.2}|\n",
+            suggestion.alias, suggestion.canonical, suggestion.similarity
+        ));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::input_stats_from_csv;
+
+    const CSV: &str = "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes
+Acme,Engineer,20220401,,,,,,,,
+ACME Inc.,Engineer,20220402,,,,,,,,
+acme corp,SWE,20220403,,,,,,,,
+Globex,Engineer,20220401,,,,,,,,
+Globex Corporation,Engineer,20220401,,,,,,,,
+Globexx,Engineer,20220401,,,,,,,,
+Initech,Engineer,20220401,,,,,,,,
+";
+
+    #[test]
+    fn test_company_key() {
+        assert_eq!("acme", company_key(" ACME Inc. "));
+        assert_eq!("acme", company_key("acme corp"));
+        // a name that is nothing but a suffix is kept
+        assert_eq!("company", company_key("Company"));
+        assert_eq!(
+            "software engineer ii",
+            position_key("Software Engineer, II")
+        );
+    }
+
+    #[test]
+    fn test_apply_aliases() {
+        let aliases: Aliases = serde_yaml::from_str(
+            "companies:\n  Acme: [ACME Inc.]\npositions:\n  Software Engineer: [swe, engineer]\n",
+        )
+        .unwrap();
+        let normalized = aliases.apply(&input_stats_from_csv(CSV));
+
+        // acme corp matches Acme by its key without being listed
+        assert_eq!("Acme", normalized[1].company);
+        assert_eq!("Acme", normalized[2].company);
+        assert_eq!("Software Engineer", normalized[2].position);
+        assert_eq!("Globex Corporation", normalized[4].company);
+    }
+
+    #[test]
+    fn test_suggest_aliases() {
+        let suggestions = suggest_aliases(
+            &input_stats_from_csv(CSV),
+            &Aliases::default(),
+            DEFAULT_SIMILARITY,
+        );
+        let pairs: Vec<(&str, &str)> = suggestions
+            .iter()
+            .map(|s| (s.alias.as_str(), s.canonical.as_str()))
+            .collect();
+
+        assert!(pairs.contains(&("ACME Inc.", "Acme")));
+        assert!(pairs.contains(&("acme corp", "Acme")));
+        assert!(pairs.contains(&("Globex Corporation", "Globex")));
+        assert!(pairs.contains(&("Globexx", "Globex")));
+        assert!(!pairs
+            .iter()
+            .any(|(a, b)| *a == "Initech" || *b == "Initech"));
+        assert_eq!(1.0, suggestions[0].similarity);
+        assert!(to_table(&suggestions).contains("|ACME Inc.|Acme|1.00|"));
+    }
+}
@@ -0,0 +1,177 @@
+use chrono::NaiveDate;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+use super::{datetime_to_f64, f64_to_datetime, InputStat};
+
+/// Dates are shifted by at most this many weeks either way
+pub const MAX_DATE_SHIFT_WEEKS: i64 = 52;
+
+/// What a referral is replaced with, the referrer's name is as identifying as the company
+const REDACTED_REFERRAL: &str = "yes";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnonymizeConfig {
+    /// keeps the pseudonyms stable between exports, without it they can be guessed by hashing
+    /// company names
+    pub salt: String,
+    /// days added to every date, 0 keeps the real dates
+    /// a whole number of weeks keeps every date on its weekday so business days are unchanged
+    pub date_shift_days: i64,
+}
+
+/// A random shift in days for a whole dataset, never 0 so the real dates are always hidden
+/// always whole weeks so business days, week cohorts and weekly goals come out the same
+pub fn random_date_shift<R: Rng>(rng: &mut R) -> i64 {
+    let shift = 7 * rng.gen_range(1..=MAX_DATE_SHIFT_WEEKS);
+    if rng.gen::<bool>() {
+        shift
+    } else {
+        -shift
+    }
+}
+
+/// A stable name for the company, the same company always gets the same pseudonym for a salt
+/// whatever its case or surrounding whitespace. 8 bytes of the hash make it very unlikely that
+/// two companies share one and get merged
+pub fn pseudonym(company: &str, salt: &str) -> String {
+    salted_name("company", company, salt)
+}
+
+/// A stable name for whose tracker a row came from, like `pseudonym` for companies
+pub fn owner_pseudonym(owner: &str, salt: &str) -> String {
+    salted_name("person", owner, salt)
+}
+
+fn salted_name(prefix: &str, name: &str, salt: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(b":");
+    hasher.update(name.trim().to_lowercase().as_bytes());
+
+    let hex: String = hasher
+        .finalize()
+        .iter()
+        .take(8)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("{}-{}", prefix, hex)
+}
+
+fn shift(date: f64, days: i64) -> f64 {
+    let shifted: NaiveDate = f64_to_datetime(date) + chrono::Duration::days(days);
+    datetime_to_f64(shifted)
+}
+
+/// Replaces everything that points at an employer, keeping what `JobStats` is computed from
+/// the company and owner become pseudonyms, the url and notes are dropped and a referral only
+/// keeps that there was one. Shifting every date by the same number of days keeps every duration
+pub fn anonymize(input_stats: &[InputStat], config: &AnonymizeConfig) -> Vec<InputStat> {
+    let days = config.date_shift_days;
+
+    input_stats
+        .iter()
+        .map(|stat| InputStat {
+            company: pseudonym(&stat.company, &config.salt),
+            applied_dt: shift(stat.applied_dt, days),
+            rejected_dt: stat.rejected_dt.map(|d| shift(d, days)),
+            // -1 records an interview i declined rather than a date
+            first_interview: stat
+                .first_interview
+                .map(|d| if d == -1.0 { d } else { shift(d, days) }),
+            offer_dt: stat.offer_dt.map(|d| shift(d, days)),
+            decision_dt: stat.decision_dt.map(|d| shift(d, days)),
+            referral: stat
+                .referral
+                .as_ref()
+                .map(|_| String::from(REDACTED_REFERRAL)),
+            url: None,
+            notes: None,
+            owner: stat
+                .owner
+                .as_ref()
+                .map(|owner| owner_pseudonym(owner, &config.salt)),
+            ..stat.clone()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::business_days::HolidayCalendar;
+    use crate::job_stat_helper::{input_stats_from_csv, JobStats};
+    use rand::SeedableRng;
+
+    const CSV: &str = "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes
+Acme,Engineer,20220228,,20220305,20220320,100000,accepted,Jane Doe,https://acme.example,met jane
+acme ,Developer,20220301,20220320,20220308,,,,,,
+Globex,Engineer,20220301,20220310,,,,,,,
+Initech,Engineer,20220301,,-1,,,,,,
+";
+
+    #[test]
+    fn test_anonymize() {
+        let mut input_stats = input_stats_from_csv(CSV);
+        input_stats[0].owner = Some(String::from("Alice"));
+        input_stats[1].owner = Some(String::from("alice "));
+        let config = AnonymizeConfig {
+            salt: String::from("pepper"),
+            date_shift_days: -28,
+        };
+        let anonymized = anonymize(&input_stats, &config);
+
+        assert_eq!(anonymized[0].company, anonymized[1].company);
+        assert_ne!(anonymized[0].company, anonymized[2].company);
+        assert_ne!(anonymized[0].company, pseudonym("Acme", "a different salt"));
+        assert!(anonymized[0].company.starts_with("company-"));
+        assert_eq!(None, anonymized[0].url);
+        assert_eq!(None, anonymized[0].notes);
+        assert_eq!(Some(String::from("yes")), anonymized[0].referral);
+        assert_eq!(20220131.0, anonymized[0].applied_dt);
+        assert_eq!(24, anonymized[0].company.len());
+        let owner = anonymized[0].owner().unwrap();
+        assert!(owner.starts_with("person-") && !owner.contains("lice"));
+        assert_eq!(anonymized[0].owner, anonymized[1].owner);
+        assert_eq!(None, anonymized[2].owner);
+        assert_eq!(Some(-1.0), anonymized[3].first_interview);
+
+        assert_eq!(
+            JobStats::new(input_stats).summary_metrics(),
+            JobStats::new(anonymized).summary_metrics()
+        );
+    }
+
+    #[test]
+    fn test_random_shift_keeps_business_days() {
+        let input_stats = input_stats_from_csv(CSV);
+        let business_days = |input_stats: &[InputStat]| {
+            JobStats::new(input_stats.to_vec())
+                .with_business_days(input_stats, &HolidayCalendar::default())
+                .to_string()
+        };
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        for _ in 0..20 {
+            let config = AnonymizeConfig {
+                salt: String::from("pepper"),
+                date_shift_days: random_date_shift(&mut rng),
+            };
+            assert_eq!(
+                business_days(&input_stats),
+                business_days(&anonymize(&input_stats, &config))
+            );
+        }
+    }
+
+    #[test]
+    fn test_random_date_shift() {
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        for _ in 0..100 {
+            let shift = random_date_shift(&mut rng);
+            assert!(shift != 0 && shift.abs() <= 7 * MAX_DATE_SHIFT_WEEKS);
+            assert_eq!(0, shift % 7);
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use chrono::{Datelike, NaiveDate, Weekday};
+
+use super::ical;
+use super::metrics::Summary;
+
+#[derive(Debug, thiserror::Error)]
+pub enum HolidayCalendarError {
+    #[error("failed to read the holiday calendar: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("line {0} of the holiday calendar is not a YYYYMMDD or YYYY-MM-DD date: {1:?}")]
+    InvalidDate(usize, String),
+}
+
+/// Days nobody is expected to reply on, weekends are always included
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HolidayCalendar {
+    pub holidays: BTreeSet<NaiveDate>,
+}
+
+impl HolidayCalendar {
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !self.holidays.contains(&date)
+    }
+}
+
+/// Reads holidays from an .ics file (every event is a holiday) or a text file with one date per
+/// line, anything after the date and lines starting with `#` are ignored
+pub fn load_holidays(path: &Path) -> Result<HolidayCalendar, HolidayCalendarError> {
+    let text = std::fs::read_to_string(path)?;
+
+    if path.extension().map_or(false, |e| e == "ics") {
+        return Ok(HolidayCalendar {
+            holidays: ical::parse_events(&text).iter().map(|e| e.start).collect(),
+        });
+    }
+
+    let mut holidays = BTreeSet::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let date = line.split_whitespace().next().unwrap_or_default();
+        let parsed = NaiveDate::parse_from_str(date, "%Y%m%d")
+            .or_else(|_| NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+            .map_err(|_| HolidayCalendarError::InvalidDate(i + 1, line.to_string()))?;
+        holidays.insert(parsed);
+    }
+
+    Ok(HolidayCalendar { holidays })
+}
+
+/// How the time between two dates is counted
+#[derive(Clone, Copy, Debug)]
+pub enum DayCount<'a> {
+    Calendar,
+    Business(&'a HolidayCalendar),
+}
+
+impl DayCount<'_> {
+    /// Days from `start` to `end`, negative if `end` comes first
+    /// in business days, a reply on monday to a friday application took 1 day
+    pub fn between(&self, start: NaiveDate, end: NaiveDate) -> i64 {
+        match self {
+            DayCount::Calendar => (end - start).num_days(),
+            DayCount::Business(calendar) => {
+                let (from, to, sign) = if start <= end {
+                    (start, end, 1)
+                } else {
+                    (end, start, -1)
+                };
+                let days = from
+                    .iter_days()
+                    .skip(1)
+                    .take_while(|d| *d <= to)
+                    .filter(|d| calendar.is_business_day(*d))
+                    .count() as i64;
+                sign * days
+            }
+        }
+    }
+}
+
+/// Mean, median, shortest and longest of one kind of duration in calendar and business days
+#[derive(Clone, Debug)]
+pub struct DurationComparison {
+    /// metric label with its calendar day and business day value
+    pub rows: Vec<(String, Option<f64>, Option<f64>)>,
+}
+
+impl DurationComparison {
+    /// `durations` names each kind of duration with its calendar day and business day samples
+    pub fn new(durations: Vec<(&str, Vec<i64>, Vec<i64>)>) -> Self {
+        let mut rows = vec![];
+        for (name, mut calendar, mut business) in durations {
+            for summary in Summary::ALL {
+                rows.push((
+                    format!("{} time between {}", summary.label(), name),
+                    summary.apply(&mut calendar),
+                    summary.apply(&mut business),
+                ));
+            }
+        }
+
+        Self { rows }
+    }
+}
+
+impl std::fmt::Display for DurationComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        writeln!(f, "|Duration|Calendar days|Business days|")?;
+        writeln!(f, "|---|---|---|")?;
+        for (label, calendar, business) in &self.rows {
+            writeln!(
+                f,
+                "|{}|{}|{}|",
+                label,
+                super::display_or_na(*calendar),
+                super::display_or_na(*business)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y%m%d").unwrap()
+    }
+
+    #[test]
+    fn test_business_days_between() {
+        let calendar = HolidayCalendar {
+            // memorial day
+            holidays: BTreeSet::from([date("20220530")]),
+        };
+        let business = DayCount::Business(&calendar);
+
+        // friday to monday
+        assert_eq!(
+            3,
+            DayCount::Calendar.between(date("20220520"), date("20220523"))
+        );
+        assert_eq!(1, business.between(date("20220520"), date("20220523")));
+        // friday to the tuesday after memorial day
+        assert_eq!(1, business.between(date("20220527"), date("20220531")));
+        assert_eq!(-1, business.between(date("20220531"), date("20220527")));
+        assert_eq!(0, business.between(date("20220528"), date("20220529")));
+        assert_eq!(5, business.between(date("20220516"), date("20220523")));
+    }
+
+    #[test]
+    fn test_load_holidays() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("holidays.txt");
+        std::fs::write(
+            &path,
+            "# us holidays\n20220530 memorial day\n2022-07-04\n\n",
+        )
+        .unwrap();
+        assert_eq!(
+            BTreeSet::from([date("20220530"), date("20220704")]),
+            load_holidays(&path).unwrap().holidays
+        );
+
+        std::fs::write(&path, "20220530\nmemorial day\n").unwrap();
+        assert!(matches!(
+            load_holidays(&path),
+            Err(HolidayCalendarError::InvalidDate(2, _))
+        ));
+
+        let ics_path = dir.path().join("holidays.ics");
+        std::fs::write(
+            &ics_path,
+            "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nSUMMARY:Memorial Day\r\nDTSTART;VALUE=DATE:20220530\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n",
+        )
+        .unwrap();
+        assert_eq!(
+            BTreeSet::from([date("20220530")]),
+            load_holidays(&ics_path).unwrap().holidays
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use chrono::NaiveDate;
+
+use super::ical::{self, Event};
+use super::{datetime_to_f64, InputStat};
+
+/// Exports every interview and offer date as an all day VEVENT
+pub fn export_ics(input_stats: &[InputStat], dtstamp: NaiveDate) -> String {
+    let mut components = vec![];
+
+    for stat in input_stats {
+        let description = [stat.url.as_deref(), stat.notes.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<&str>>()
+            .join("\n");
+
+        let dated_events = [
+            ("interview", "Interview", stat.first_interview_date()),
+            ("offer", "Offer", stat.offer_date()),
+        ];
+
+        for (uid_prefix, label, date) in dated_events {
+            let date = match date {
+                Some(d) => d,
+                None => continue,
+            };
+
+            let mut vevent = String::new();
+            ical::push_line(&mut vevent, "BEGIN:VEVENT");
+            ical::push_line(
+                &mut vevent,
+                &format!("UID:{}-{}@job_stats", uid_prefix, stat.application_id()),
+            );
+            ical::push_line(
+                &mut vevent,
+                &format!("DTSTAMP:{}", ical::date_time_value(dtstamp)),
+            );
+            ical::push_line(
+                &mut vevent,
+                &format!("DTSTART;VALUE=DATE:{}", ical::date_value(date)),
+            );
+            ical::push_line(
+                &mut vevent,
+                &format!(
+                    "DTEND;VALUE=DATE:{}",
+                    ical::date_value(date + chrono::Duration::days(1))
+                ),
+            );
+            ical::push_line(
+                &mut vevent,
+                &format!(
+                    "SUMMARY:{}",
+                    ical::escape_text(&format!("{}: {} - {}", label, stat.company, stat.position))
+                ),
+            );
+            if !description.is_empty() {
+                ical::push_line(
+                    &mut vevent,
+                    &format!("DESCRIPTION:{}", ical::escape_text(&description)),
+                );
+            }
+            ical::push_line(&mut vevent, "END:VEVENT");
+            components.push(vevent);
+        }
+    }
+
+    ical::calendar(&components)
+}
+
+/// A first interview date found in a calendar for an application that had none
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InterviewUpdate {
+    /// index of the record in the input stats
+    pub index: usize,
+    pub application_id: String,
+    pub company: String,
+    pub position: String,
+    pub first_interview: NaiveDate,
+    /// the summary of the calendar event the date came from
+    pub event_summary: String,
+}
+
+/// Matches calendar events to applications with no first interview
+/// an event matches when its summary contains the company name (ignoring case). When a company
+/// has several open applications the most recent one applied to before the event wins
+pub fn match_interviews(input_stats: &[InputStat], events: &[Event]) -> Vec<InterviewUpdate> {
+    let mut events = events.to_vec();
+    events.sort_by_key(|e| e.start);
+
+    let mut updates: Vec<InterviewUpdate> = vec![];
+    for event in &events {
+        let summary = event.summary.to_lowercase();
+
+        let candidate = input_stats
+            .iter()
+            .enumerate()
+            .filter(|(i, stat)| {
+                stat.first_interview.is_none()
+                    && !stat.company.trim().is_empty()
+                    && summary.contains(&stat.company.trim().to_lowercase())
+                    && stat.applied_date() <= event.start
+                    && stat
+                        .rejected_date()
+                        .map_or(true, |rejected| rejected >= event.start)
+                    && !updates.iter().any(|u| u.index == *i)
+            })
+            .max_by_key(|(_, stat)| stat.applied_date());
+
+        if let Some((index, stat)) = candidate {
+            updates.push(InterviewUpdate {
+                index,
+                application_id: stat.application_id(),
+                company: stat.company.clone(),
+                position: stat.position.clone(),
+                first_interview: event.start,
+                event_summary: event.summary.clone(),
+            });
+        }
+    }
+
+    updates.sort_by_key(|u| u.index);
+    updates
+}
+
+/// Renders the updates as a diff so they can be checked before being applied
+pub fn updates_diff(updates: &[InterviewUpdate]) -> String {
+    let mut out = String::new();
+    for update in updates {
+        out.push_str(&format!(
+            "{} ({} - {}) from \"{}\"\n",
+            update.application_id, update.company, update.position, update.event_summary
+        ));
+        out.push_str("- first_interview:\n");
+        out.push_str(&format!(
+            "+ first_interview: {}\n",
+            update.first_interview.format("%Y%m%d")
+        ));
+    }
+
+    out
+}
+
+/// Writes the matched first interview dates into the input stats
+pub fn apply_interview_updates(input_stats: &mut [InputStat], updates: &[InterviewUpdate]) {
+    for update in updates {
+        if let Some(stat) = input_stats.get_mut(update.index) {
+            stat.first_interview = Some(datetime_to_f64(update.first_interview));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::input_stats_from_csv;
+
+    const CSV: &str = "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes
+Acme,Engineer,20220301,20220315,,,,,,,
+Acme,Developer,20220401,,,,,,,,
+Globex,Developer,20220401,,20220410,20220420,,,,https://globex.example,\"great, team\"
+";
+
+    #[test]
+    fn test_export_ics() {
+        let input_stats = input_stats_from_csv(CSV);
+        let ics = export_ics(&input_stats, NaiveDate::from_ymd_opt(2022, 5, 1).unwrap());
+
+        assert_eq!(2, ics.matches("BEGIN:VEVENT").count());
+        assert!(ics.contains("SUMMARY:Interview: Globex - Developer\r\n"));
+        assert!(ics.contains("SUMMARY:Offer: Globex - Developer\r\n"));
+        assert!(ics.contains("DESCRIPTION:https://globex.example\\ngreat\\, team\r\n"));
+
+        // the export reads back as the same dates
+        let starts: Vec<NaiveDate> = ical::parse_events(&ics).iter().map(|e| e.start).collect();
+        assert_eq!(
+            vec![
+                NaiveDate::from_ymd_opt(2022, 4, 10).unwrap(),
+                NaiveDate::from_ymd_opt(2022, 4, 20).unwrap()
+            ],
+            starts
+        );
+    }
+
+    #[test]
+    fn test_match_and_apply_interviews() {
+        let mut input_stats = input_stats_from_csv(CSV);
+        let events = vec![
+            Event {
+                summary: String::from("Phone screen with ACME"),
+                description: None,
+                start: NaiveDate::from_ymd_opt(2022, 4, 12).unwrap(),
+            },
+            Event {
+                summary: String::from("Dentist"),
+                description: None,
+                start: NaiveDate::from_ymd_opt(2022, 4, 13).unwrap(),
+            },
+        ];
+
+        let updates = match_interviews(&input_stats, &events);
+        assert_eq!(1, updates.len());
+        assert_eq!(1, updates[0].index);
+        assert_eq!("acme-developer-20220401", updates[0].application_id);
+
+        apply_interview_updates(&mut input_stats, &updates);
+        assert_eq!(Some(20220412.0), input_stats[1].first_interview);
+        assert_eq!(None, input_stats[0].first_interview);
+    }
+}
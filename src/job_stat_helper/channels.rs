@@ -0,0 +1,215 @@
+use std::collections::BTreeMap;
+
+use super::{mean, median, significance, team, InputStat, ValidationWarning};
+
+/// Where an application came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Channel {
+    JobBoard,
+    CompanySite,
+    RecruiterInbound,
+    /// the `referral` column says who referred me
+    Referral,
+    Meetup,
+}
+
+impl std::str::FromStr for Channel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace([' ', '_'], "-").as_str() {
+            "job-board" => Ok(Channel::JobBoard),
+            "company-site" => Ok(Channel::CompanySite),
+            "recruiter" | "recruiter-inbound" => Ok(Channel::RecruiterInbound),
+            "referral" => Ok(Channel::Referral),
+            "meetup" => Ok(Channel::Meetup),
+            other => Err(format!(
+                "unknown channel {other}, expected job-board, company-site, recruiter-inbound, referral or meetup"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for Channel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        let name = match self {
+            Channel::JobBoard => "job-board",
+            Channel::CompanySite => "company-site",
+            Channel::RecruiterInbound => "recruiter-inbound",
+            Channel::Referral => "referral",
+            Channel::Meetup => "meetup",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Warns about a `channel` column that is not a known channel
+pub(super) fn validate(stat: &InputStat) -> Vec<ValidationWarning> {
+    match stat.channel() {
+        Some(Err(message)) => vec![ValidationWarning {
+            application_id: stat.application_id(),
+            message,
+        }],
+        _ => vec![],
+    }
+}
+
+/// Fills in the `channel` column of applications recorded before it existed, a row with a
+/// referral came through one
+pub fn migrate_referrals(input_stats: &[InputStat]) -> Vec<InputStat> {
+    input_stats
+        .iter()
+        .map(|stat| {
+            let mut stat = stat.clone();
+            if stat.channel.is_none() && stat.referral.is_some() {
+                stat.channel = Some(Channel::Referral.to_string());
+            }
+            stat
+        })
+        .collect()
+}
+
+/// How applications from one channel went
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelOutcomes {
+    /// `None` for applications with no channel recorded
+    pub channel: Option<Channel>,
+    pub applied: u32,
+    pub responses: u32,
+    pub offers: u32,
+    pub mean_days_to_first_interview: Option<f64>,
+    pub median_days_to_first_interview: Option<f64>,
+}
+
+/// Volume, response rate, time to first interview and offer rate of every channel
+#[derive(Clone, Debug)]
+pub struct ChannelStats {
+    /// most applications first
+    pub channels: Vec<ChannelOutcomes>,
+}
+
+impl ChannelStats {
+    /// applications with a channel that is not recognized are left out, `validate` warns about
+    /// them
+    pub fn new(input_stats: &[InputStat]) -> Self {
+        let mut by_channel: BTreeMap<Option<Channel>, Vec<&InputStat>> = BTreeMap::new();
+        for stat in input_stats {
+            match stat.channel() {
+                Some(Err(_)) => continue,
+                channel => by_channel
+                    .entry(channel.and_then(Result::ok))
+                    .or_default()
+                    .push(stat),
+            }
+        }
+
+        let mut channels: Vec<ChannelOutcomes> = by_channel
+            .into_iter()
+            .map(|(channel, stats)| {
+                let mut days_to_first_interview: Vec<i64> = stats
+                    .iter()
+                    .filter_map(|s| {
+                        s.first_interview_date()
+                            .map(|d| (d - s.applied_date()).num_days())
+                    })
+                    .collect();
+
+                ChannelOutcomes {
+                    channel,
+                    applied: stats.len() as u32,
+                    responses: stats.iter().filter(|s| team::responded(s)).count() as u32,
+                    offers: stats.iter().filter(|s| s.offer_dt.is_some()).count() as u32,
+                    mean_days_to_first_interview: mean(&days_to_first_interview),
+                    median_days_to_first_interview: median(&mut days_to_first_interview),
+                }
+            })
+            .collect();
+        channels.sort_by(|a, b| b.applied.cmp(&a.applied).then(a.channel.cmp(&b.channel)));
+
+        Self { channels }
+    }
+}
+
+impl std::fmt::Display for ChannelStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        writeln!(
+            f,
+            "|Channel|Jobs applied to|Response rate|Mean days to first interview|Median days to first interview|Offer rate|"
+        )?;
+        writeln!(f, "|---|---|---|---|---|---|")?;
+        for outcomes in &self.channels {
+            writeln!(
+                f,
+                "|{}|{}|{}|{}|{}|{}|",
+                outcomes
+                    .channel
+                    .map_or_else(|| String::from("unknown"), |c| c.to_string()),
+                outcomes.applied,
+                significance::format_rate(outcomes.responses, outcomes.applied),
+                super::display_or_na(outcomes.mean_days_to_first_interview),
+                super::display_or_na(outcomes.median_days_to_first_interview),
+                significance::format_rate(outcomes.offers, outcomes.applied)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::input_stats_from_csv;
+
+    const CSV: &str = "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes,channel
+A,Engineer,20220401,,20220405,20220420,,,Jane Doe,,,
+B,Engineer,20220401,,20220411,,,,,,,Referral
+C,Engineer,20220401,20220410,,,,,,,,job board
+D,Engineer,20220401,,,,,,,,,job-board
+E,Engineer,20220401,,20220403,,,,,,,recruiter
+F,Engineer,20220401,,,,,,,,,
+G,Engineer,20220401,,,,,,,,,newsletter
+";
+
+    #[test]
+    fn test_channel() {
+        let input_stats = input_stats_from_csv(CSV);
+        // a referral recorded before there was a channel column
+        assert_eq!(Some(Ok(Channel::Referral)), input_stats[0].channel());
+        assert_eq!(Some(Ok(Channel::JobBoard)), input_stats[2].channel());
+        assert_eq!(None, input_stats[5].channel());
+        assert_eq!(1, validate(&input_stats[6]).len());
+        assert!(validate(&input_stats[4]).is_empty());
+
+        let migrated = migrate_referrals(&input_stats);
+        assert_eq!(Some(String::from("referral")), migrated[0].channel);
+        assert_eq!(None, migrated[5].channel);
+    }
+
+    #[test]
+    fn test_channel_stats() {
+        let stats = ChannelStats::new(&input_stats_from_csv(CSV));
+
+        assert_eq!(4, stats.channels.len());
+        assert_eq!(
+            ChannelOutcomes {
+                channel: Some(Channel::Referral),
+                applied: 2,
+                responses: 2,
+                offers: 1,
+                mean_days_to_first_interview: Some(7.0),
+                median_days_to_first_interview: Some(7.0),
+            },
+            *stats
+                .channels
+                .iter()
+                .find(|c| c.channel == Some(Channel::Referral))
+                .unwrap()
+        );
+        assert_eq!(1, stats.channels[3].applied);
+        let rendered = stats.to_string();
+        assert!(rendered.contains("|job-board|2|1/2 = 50.00%"));
+        assert!(rendered.contains("|unknown|1|0/1 = 0.00%"));
+    }
+}
@@ -0,0 +1,240 @@
+use chrono::{Datelike, NaiveDate};
+
+use super::InputStat;
+
+/// How many days after applying each cohort is checked at
+pub const CHECKPOINT_DAYS: [i64; 4] = [7, 14, 30, 60];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CohortPeriod {
+    /// ISO weeks starting on monday
+    Week,
+    Month,
+}
+
+impl CohortPeriod {
+    /// the first day of the period the date falls in
+    fn start_of(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            CohortPeriod::Week => {
+                date - chrono::Duration::days(date.weekday().num_days_from_monday().into())
+            }
+            CohortPeriod::Month => date.with_day(1).unwrap(),
+        }
+    }
+
+    fn label(&self, start: NaiveDate) -> String {
+        match self {
+            CohortPeriod::Week => {
+                let week = start.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            CohortPeriod::Month => start.format("%Y-%m").to_string(),
+        }
+    }
+}
+
+impl std::str::FromStr for CohortPeriod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "week" => Ok(CohortPeriod::Week),
+            "month" => Ok(CohortPeriod::Month),
+            other => Err(format!(
+                "unknown cohort period {other}, expected week or month"
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Milestone {
+    FirstInterview,
+    Rejection,
+    Offer,
+}
+
+impl Milestone {
+    const ALL: [Milestone; 3] = [
+        Milestone::FirstInterview,
+        Milestone::Rejection,
+        Milestone::Offer,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Milestone::FirstInterview => "First interview",
+            Milestone::Rejection => "Rejection",
+            Milestone::Offer => "Offer",
+        }
+    }
+
+    fn date(&self, stat: &InputStat) -> Option<NaiveDate> {
+        match self {
+            Milestone::FirstInterview => stat.first_interview_date(),
+            Milestone::Rejection => stat.rejected_date(),
+            Milestone::Offer => stat.offer_date(),
+        }
+    }
+}
+
+/// The applications sent during one week or month
+#[derive(Clone, Debug)]
+pub struct Cohort {
+    pub start: NaiveDate,
+    pub size: u32,
+    /// the last application in the cohort, a checkpoint is only complete once it has aged past it
+    latest_applied: NaiveDate,
+    /// days from application to each milestone, one entry per application that reached it
+    days_to: [Vec<i64>; 3],
+}
+
+impl Cohort {
+    /// The share of the cohort that reached the milestone within `days` of applying
+    /// `None` until every application in the cohort is at least `days` old
+    pub fn share(&self, milestone: Milestone, days: i64, today: NaiveDate) -> Option<f64> {
+        if (today - self.latest_applied).num_days() < days || self.size == 0 {
+            return None;
+        }
+
+        let reached = self.days_to[milestone as usize]
+            .iter()
+            .filter(|d| **d <= days)
+            .count();
+
+        Some(reached as f64 / self.size as f64)
+    }
+}
+
+/// Retention style table of how quickly each cohort of applications got responses
+#[derive(Clone, Debug)]
+pub struct CohortTable {
+    pub period: CohortPeriod,
+    pub today: NaiveDate,
+    pub cohorts: Vec<Cohort>,
+}
+
+impl CohortTable {
+    pub fn new(input_stats: &[InputStat], period: CohortPeriod, today: NaiveDate) -> Self {
+        let mut cohorts: Vec<Cohort> = vec![];
+
+        for stat in input_stats {
+            let applied = stat.applied_date();
+            let start = period.start_of(applied);
+
+            let index = match cohorts.iter().position(|c| c.start == start) {
+                Some(i) => i,
+                None => {
+                    cohorts.push(Cohort {
+                        start,
+                        size: 0,
+                        latest_applied: applied,
+                        days_to: Default::default(),
+                    });
+                    cohorts.len() - 1
+                }
+            };
+
+            let cohort = &mut cohorts[index];
+            cohort.size += 1;
+            cohort.latest_applied = cohort.latest_applied.max(applied);
+            for milestone in Milestone::ALL {
+                if let Some(date) = milestone.date(stat) {
+                    cohort.days_to[milestone as usize].push((date - applied).num_days());
+                }
+            }
+        }
+
+        cohorts.sort_by_key(|c| c.start);
+
+        Self {
+            period,
+            today,
+            cohorts,
+        }
+    }
+}
+
+impl std::fmt::Display for CohortTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        for (i, milestone) in Milestone::ALL.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+
+            write!(f, "|{} by cohort|Applied|", milestone.label())?;
+            for days in CHECKPOINT_DAYS {
+                write!(f, "{} days|", days)?;
+            }
+            writeln!(f)?;
+            writeln!(f, "|---|---|{}", "---|".repeat(CHECKPOINT_DAYS.len()))?;
+
+            for cohort in &self.cohorts {
+                write!(f, "|{}|{}|", self.period.label(cohort.start), cohort.size)?;
+                for days in CHECKPOINT_DAYS {
+                    match cohort.share(*milestone, days, self.today) {
+                        Some(share) => write!(f, "{:.2}%|", share * 100.0)?,
+                        None => write!(f, "-|")?,
+                    }
+                }
+                writeln!(f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::input_stats_from_csv;
+
+    const CSV: &str = "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes
+A,Engineer,20220404,,20220408,20220501,,,,,
+B,Engineer,20220406,20220425,,,,,,,
+C,Engineer,20220410,,,,,,,,
+D,Engineer,20220411,,20220430,,,,,,
+";
+
+    #[test]
+    fn test_weekly_cohorts() {
+        let input_stats = input_stats_from_csv(CSV);
+        let today = NaiveDate::from_ymd_opt(2022, 5, 15).unwrap();
+        let table = CohortTable::new(&input_stats, CohortPeriod::Week, today);
+
+        assert_eq!(2, table.cohorts.len());
+        let first = &table.cohorts[0];
+        assert_eq!(NaiveDate::from_ymd_opt(2022, 4, 4).unwrap(), first.start);
+        assert_eq!(3, first.size);
+        assert_eq!(
+            Some(1.0 / 3.0),
+            first.share(Milestone::FirstInterview, 7, today)
+        );
+        assert_eq!(Some(0.0), first.share(Milestone::Rejection, 14, today));
+        assert_eq!(
+            Some(1.0 / 3.0),
+            first.share(Milestone::Rejection, 30, today)
+        );
+        assert_eq!(Some(1.0 / 3.0), first.share(Milestone::Offer, 30, today));
+        // the cohort has not been around for 60 days yet
+        assert_eq!(None, first.share(Milestone::Offer, 60, today));
+
+        let rendered = table.to_string();
+        assert!(rendered.contains("|2022-W14|3|33.33%|33.33%|33.33%|-|"));
+        assert!(rendered.contains("|2022-W15|1|0.00%|0.00%|100.00%|-|"));
+    }
+
+    #[test]
+    fn test_monthly_cohorts() {
+        let input_stats = input_stats_from_csv(CSV);
+        let today = NaiveDate::from_ymd_opt(2022, 5, 15).unwrap();
+        let table = CohortTable::new(&input_stats, CohortPeriod::Month, today);
+
+        assert_eq!(1, table.cohorts.len());
+        assert_eq!(4, table.cohorts[0].size);
+        assert!(table.to_string().contains("|2022-04|4|"));
+    }
+}
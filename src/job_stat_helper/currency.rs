@@ -0,0 +1,350 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::offers::OfferDetails;
+use super::{InputStat, ValidationWarning};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ExchangeRateError {
+    #[error("failed to read the exchange rate file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the exchange rate file is not valid yaml: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("the exchange rate file is not valid csv: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("the rate from {0} to {1} must be a positive number")]
+    InvalidRate(String, String),
+}
+
+/// One unit of `from` is worth `rate` units of `to`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExchangeRate {
+    pub from: String,
+    pub to: String,
+    pub rate: f64,
+}
+
+/// A local table of exchange rates, nothing is looked up over the network
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExchangeRates {
+    rates: Vec<ExchangeRate>,
+}
+
+impl ExchangeRates {
+    pub fn new(rates: Vec<ExchangeRate>) -> Result<Self, ExchangeRateError> {
+        let rates = rates
+            .into_iter()
+            .map(|r| {
+                if r.rate.is_finite() && r.rate > 0.0 {
+                    Ok(ExchangeRate {
+                        from: r.from.to_uppercase(),
+                        to: r.to.to_uppercase(),
+                        rate: r.rate,
+                    })
+                } else {
+                    Err(ExchangeRateError::InvalidRate(r.from, r.to))
+                }
+            })
+            .collect::<Result<Vec<ExchangeRate>, ExchangeRateError>>()?;
+
+        Ok(Self { rates })
+    }
+
+    /// How many units of `to` one unit of `from` is worth
+    /// uses the rate either way round and goes through one other currency when there is no
+    /// rate between the two directly
+    pub fn rate(&self, from: &str, to: &str) -> Option<f64> {
+        let (from, to) = (from.to_uppercase(), to.to_uppercase());
+        if from == to {
+            return Some(1.0);
+        }
+
+        self.direct(&from, &to).or_else(|| {
+            self.currencies()
+                .into_iter()
+                .find_map(|via| Some(self.direct(&from, via)? * self.direct(via, &to)?))
+        })
+    }
+
+    fn direct(&self, from: &str, to: &str) -> Option<f64> {
+        self.rates.iter().find_map(|r| {
+            if r.from == from && r.to == to {
+                Some(r.rate)
+            } else if r.from == to && r.to == from {
+                Some(1.0 / r.rate)
+            } else {
+                None
+            }
+        })
+    }
+
+    fn currencies(&self) -> Vec<&str> {
+        let mut currencies: Vec<&str> = self
+            .rates
+            .iter()
+            .flat_map(|r| [r.from.as_str(), r.to.as_str()])
+            .collect();
+        currencies.sort_unstable();
+        currencies.dedup();
+        currencies
+    }
+}
+
+/// Reads a `from,to,rate` csv, or a yaml list of the same, picked by the file extension
+pub fn load_exchange_rates(path: &Path) -> Result<ExchangeRates, ExchangeRateError> {
+    let rates = if path.extension().map_or(false, |e| e == "csv") {
+        csv::Reader::from_path(path)?
+            .deserialize()
+            .collect::<Result<Vec<ExchangeRate>, csv::Error>>()?
+    } else {
+        serde_yaml::from_str(&std::fs::read_to_string(path)?)?
+    };
+
+    ExchangeRates::new(rates)
+}
+
+/// Converts every offer amount to the reporting currency
+/// an amount with no currency is taken to already be in it. An amount with no rate to convert
+/// it is dropped, so it can not skew the offer stats, and flagged in the returned warnings
+pub fn normalize(
+    input_stats: &[InputStat],
+    rates: &ExchangeRates,
+    reporting: &str,
+) -> (Vec<InputStat>, Vec<ValidationWarning>) {
+    let reporting = reporting.to_uppercase();
+    let mut warnings = vec![];
+
+    let normalized = input_stats
+        .iter()
+        .map(|stat| {
+            let mut stat = stat.clone();
+            let amount = match stat.offer_amt {
+                Some(a) => a,
+                None => return stat,
+            };
+            let currency = stat
+                .currency
+                .as_deref()
+                .map_or_else(|| reporting.clone(), str::to_uppercase);
+
+            match rates.rate(&currency, &reporting) {
+                Some(rate) => stat.offer_amt = Some(amount * rate),
+                None => {
+                    warnings.push(ValidationWarning {
+                        application_id: stat.application_id(),
+                        message: format!(
+                            "no exchange rate from {currency} to {reporting}, the offer amount is left out"
+                        ),
+                    });
+                    stat.offer_amt = None;
+                }
+            }
+            stat.currency = Some(reporting.clone());
+            stat
+        })
+        .collect();
+
+    (normalized, warnings)
+}
+
+/// Converts every amount of the offer details to the reporting currency
+/// details with no currency are taken to already be in it. Details with no rate to convert them
+/// are dropped, so they can not be compared in the wrong currency, and flagged in the warnings
+pub fn normalize_offer_details(
+    details: &[OfferDetails],
+    rates: &ExchangeRates,
+    reporting: &str,
+) -> (Vec<OfferDetails>, Vec<ValidationWarning>) {
+    let reporting = reporting.to_uppercase();
+    let mut warnings = vec![];
+
+    let normalized = details
+        .iter()
+        .filter_map(|offer| {
+            let currency = offer
+                .currency
+                .as_deref()
+                .map_or_else(|| reporting.clone(), str::to_uppercase);
+
+            match rates.rate(&currency, &reporting) {
+                Some(rate) => Some(OfferDetails {
+                    currency: Some(reporting.clone()),
+                    ..offer.scaled(rate)
+                }),
+                None => {
+                    warnings.push(ValidationWarning {
+                        application_id: offer.application_id.clone(),
+                        message: format!(
+                            "no exchange rate from {currency} to {reporting}, the offer details are left out"
+                        ),
+                    });
+                    None
+                }
+            }
+        })
+        .collect();
+
+    (normalized, warnings)
+}
+
+/// Warns when offer details in different currencies would be compared side by side
+pub fn validate_offer_details(details: &[OfferDetails]) -> Vec<ValidationWarning> {
+    let mut currencies: Vec<String> = details
+        .iter()
+        .filter_map(|d| d.currency.as_deref().map(str::to_uppercase))
+        .collect();
+    currencies.sort_unstable();
+    currencies.dedup();
+
+    if currencies.len() < 2 {
+        return vec![];
+    }
+
+    vec![ValidationWarning {
+        application_id: String::from("all"),
+        message: format!(
+            "offer details are in {}, normalize them to one currency before comparing",
+            currencies.join(", ")
+        ),
+    }]
+}
+
+/// Warns when offer amounts in different currencies would be averaged together
+pub fn validate(input_stats: &[InputStat]) -> Vec<ValidationWarning> {
+    let mut currencies: Vec<String> = input_stats
+        .iter()
+        .filter(|s| s.offer_amt.is_some())
+        .filter_map(|s| s.currency.as_deref().map(str::to_uppercase))
+        .collect();
+    currencies.sort_unstable();
+    currencies.dedup();
+
+    if currencies.len() < 2 {
+        return vec![];
+    }
+
+    vec![ValidationWarning {
+        application_id: String::from("all"),
+        message: format!(
+            "offer amounts are in {}, normalize them to one currency before comparing",
+            currencies.join(", ")
+        ),
+    }]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::input_stats_from_csv;
+
+    fn rates() -> ExchangeRates {
+        ExchangeRates::new(vec![
+            ExchangeRate {
+                from: String::from("EUR"),
+                to: String::from("USD"),
+                rate: 1.25,
+            },
+            ExchangeRate {
+                from: String::from("usd"),
+                to: String::from("gbp"),
+                rate: 0.8,
+            },
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rates() {
+        let rates = rates();
+        assert_eq!(Some(1.0), rates.rate("JPY", "jpy"));
+        assert_eq!(Some(1.25), rates.rate("eur", "USD"));
+        assert_eq!(Some(0.8), rates.rate("USD", "EUR"));
+        // through USD
+        assert_eq!(Some(1.0), rates.rate("EUR", "GBP"));
+        assert_eq!(None, rates.rate("JPY", "USD"));
+    }
+
+    #[test]
+    fn test_normalize() {
+        let input_stats = input_stats_from_csv(
+            "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,currency,accepted,referral,url,notes
+A,Engineer,20220401,,20220405,20220420,100000,EUR,,,,
+B,Engineer,20220401,,20220405,20220420,100000,,,,,
+C,Engineer,20220401,,20220405,20220420,100000,JPY,,,,
+D,Engineer,20220401,,,,,,,,,
+",
+        );
+        assert_eq!(1, validate(&input_stats).len());
+
+        let (normalized, warnings) = normalize(&input_stats, &rates(), "usd");
+        assert_eq!(Some(125000.0), normalized[0].offer_amt);
+        assert_eq!(Some(100000.0), normalized[1].offer_amt);
+        assert_eq!(None, normalized[2].offer_amt);
+        assert_eq!(Some(String::from("USD")), normalized[0].currency);
+        assert_eq!(None, normalized[3].currency);
+        assert!(validate(&normalized).is_empty());
+
+        assert_eq!(1, warnings.len());
+        assert_eq!("c-engineer-20220401", warnings[0].application_id);
+        assert!(warnings[0]
+            .message
+            .contains("no exchange rate from JPY to USD"));
+    }
+
+    #[test]
+    fn test_normalize_offer_details() {
+        let details: Vec<OfferDetails> = serde_yaml::from_str(
+            "
+- application_id: a
+  base: 100000
+  bonus: 10000
+  equity:
+    total: 40000
+  currency: eur
+- application_id: b
+  base: 100000
+- application_id: c
+  base: 100000
+  currency: JPY
+",
+        )
+        .unwrap();
+        assert_eq!(1, validate_offer_details(&details).len());
+
+        let (normalized, warnings) = normalize_offer_details(&details, &rates(), "USD");
+        assert_eq!(2, normalized.len());
+        assert_eq!(125000.0, normalized[0].base);
+        assert_eq!(12500.0, normalized[0].bonus);
+        assert_eq!(50000.0, normalized[0].equity.as_ref().unwrap().total);
+        assert_eq!(Some(String::from("USD")), normalized[0].currency);
+        assert_eq!(100000.0, normalized[1].base);
+        assert!(validate_offer_details(&normalized).is_empty());
+
+        assert_eq!(1, warnings.len());
+        assert_eq!("c", warnings[0].application_id);
+    }
+
+    #[test]
+    fn test_load_exchange_rates() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("rates.csv");
+        std::fs::write(&csv_path, "from,to,rate\nEUR,USD,1.25\nUSD,GBP,0.8\n").unwrap();
+        let yaml_path = dir.path().join("rates.yaml");
+        std::fs::write(
+            &yaml_path,
+            "- from: EUR\n  to: USD\n  rate: 1.25\n- from: USD\n  to: GBP\n  rate: 0.8\n",
+        )
+        .unwrap();
+
+        assert_eq!(rates(), load_exchange_rates(&csv_path).unwrap());
+        assert_eq!(rates(), load_exchange_rates(&yaml_path).unwrap());
+
+        std::fs::write(&csv_path, "from,to,rate\nEUR,USD,0\n").unwrap();
+        assert!(matches!(
+            load_exchange_rates(&csv_path),
+            Err(ExchangeRateError::InvalidRate(..))
+        ));
+    }
+}
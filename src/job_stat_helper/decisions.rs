@@ -0,0 +1,284 @@
+use chrono::NaiveDate;
+
+use super::{display_or_na, mean, median, significance, InputStat, ValidationWarning};
+
+/// What became of an offer, read from the `accepted` column
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OfferDecision {
+    Accepted,
+    Declined,
+    /// still talking, the offer is pending
+    Negotiating,
+    /// i let the offer run out
+    Expired,
+    /// the company took the offer back
+    Rescinded,
+}
+
+impl OfferDecision {
+    /// whether i made the call, a rescinded offer or one still being negotiated was not my decision
+    fn decided_by_me(&self) -> bool {
+        matches!(
+            self,
+            OfferDecision::Accepted | OfferDecision::Declined | OfferDecision::Expired
+        )
+    }
+}
+
+impl std::str::FromStr for OfferDecision {
+    type Err = String;
+
+    /// also takes the yes/no values the column held before it was typed
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "accepted" | "yes" | "y" | "true" => Ok(OfferDecision::Accepted),
+            "declined" | "no" | "n" | "false" => Ok(OfferDecision::Declined),
+            "negotiating" => Ok(OfferDecision::Negotiating),
+            "expired" => Ok(OfferDecision::Expired),
+            "rescinded" => Ok(OfferDecision::Rescinded),
+            other => Err(format!(
+                "unknown offer decision {other}, expected accepted, declined, negotiating, expired or rescinded"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for OfferDecision {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        let name = match self {
+            OfferDecision::Accepted => "accepted",
+            OfferDecision::Declined => "declined",
+            OfferDecision::Negotiating => "negotiating",
+            OfferDecision::Expired => "expired",
+            OfferDecision::Rescinded => "rescinded",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Problems with the `accepted` and `decision_dt` columns of one application
+pub(super) fn validate(stat: &InputStat) -> Vec<ValidationWarning> {
+    let mut warnings = vec![];
+    let warn = |message: String| ValidationWarning {
+        application_id: stat.application_id(),
+        message,
+    };
+
+    if let Some(Err(e)) = stat.offer_decision() {
+        warnings.push(warn(e));
+    }
+    if stat.accepted.is_some() && stat.offer_dt.is_none() {
+        warnings.push(warn(String::from(
+            "has an offer decision but no offer date",
+        )));
+    }
+    if let Some(decision) = stat.decision_date() {
+        if stat.accepted.is_none() {
+            warnings.push(warn(String::from(
+                "has a decision date but no offer decision",
+            )));
+        }
+        if stat.offer_date().map_or(false, |offer| decision < offer) {
+            warnings.push(warn(format!(
+                "decision date {decision} is before the offer date"
+            )));
+        }
+    }
+
+    warnings
+}
+
+/// An offer without a final decision
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingOffer {
+    pub company: String,
+    pub position: String,
+    pub offer_date: NaiveDate,
+    pub offer_amt: Option<f64>,
+    pub negotiating: bool,
+}
+
+/// How offers were decided on
+#[derive(Clone, Debug)]
+pub struct DecisionStats {
+    pub offers: u32,
+    pub accepted: u32,
+    pub declined: u32,
+    pub expired: u32,
+    pub rescinded: u32,
+    pub mean_days_offer_to_decision: Option<f64>,
+    pub median_days_offer_to_decision: Option<f64>,
+    pub pending: Vec<PendingOffer>,
+}
+
+impl DecisionStats {
+    pub fn new(input_stats: &[InputStat]) -> Self {
+        let mut stats = Self {
+            offers: 0,
+            accepted: 0,
+            declined: 0,
+            expired: 0,
+            rescinded: 0,
+            mean_days_offer_to_decision: None,
+            median_days_offer_to_decision: None,
+            pending: vec![],
+        };
+        let mut days_to_decision = vec![];
+
+        for stat in input_stats {
+            let offer_date = match stat.offer_date() {
+                Some(o) => o,
+                None => continue,
+            };
+            stats.offers += 1;
+
+            // an unrecognized decision is reported by validation and treated as pending here
+            let decision = stat.offer_decision().and_then(Result::ok);
+            match decision {
+                Some(OfferDecision::Accepted) => stats.accepted += 1,
+                Some(OfferDecision::Declined) => stats.declined += 1,
+                Some(OfferDecision::Expired) => stats.expired += 1,
+                Some(OfferDecision::Rescinded) => stats.rescinded += 1,
+                Some(OfferDecision::Negotiating) | None => stats.pending.push(PendingOffer {
+                    company: stat.company.clone(),
+                    position: stat.position.clone(),
+                    offer_date,
+                    offer_amt: stat.offer_amt,
+                    negotiating: decision.is_some(),
+                }),
+            }
+
+            if let (Some(_), Some(decided)) = (
+                decision.filter(OfferDecision::decided_by_me),
+                stat.decision_date(),
+            ) {
+                days_to_decision.push((decided - offer_date).num_days());
+            }
+        }
+
+        stats.mean_days_offer_to_decision = mean(&days_to_decision);
+        stats.median_days_offer_to_decision = median(&mut days_to_decision);
+        stats.pending.sort_by_key(|p| p.offer_date);
+
+        stats
+    }
+
+    /// offers i accepted out of every offer i made a call on
+    pub fn decided_by_me(&self) -> u32 {
+        self.accepted + self.declined + self.expired
+    }
+}
+
+impl std::fmt::Display for DecisionStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        writeln!(f, "|Offer decisions|Value|")?;
+        writeln!(f, "|---|---|")?;
+        writeln!(f, "|Offers|{}|", self.offers)?;
+        writeln!(
+            f,
+            "|Accepted / declined / expired / rescinded|{} / {} / {} / {}|",
+            self.accepted, self.declined, self.expired, self.rescinded
+        )?;
+        writeln!(
+            f,
+            "|Offer acceptance rate (of offers I decided on)|{}|",
+            significance::format_rate(self.accepted, self.decided_by_me())
+        )?;
+        writeln!(
+            f,
+            "|Mean time (days) between offer and decision|{}|",
+            display_or_na(self.mean_days_offer_to_decision)
+        )?;
+        writeln!(
+            f,
+            "|Median time (days) between offer and decision|{}|",
+            display_or_na(self.median_days_offer_to_decision)
+        )?;
+        writeln!(f, "|Offers pending decision|{}|", self.pending.len())?;
+
+        if !self.pending.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "|Pending offer|Offered on|Amount|Status|")?;
+            writeln!(f, "|---|---|---|---|")?;
+            for offer in &self.pending {
+                writeln!(
+                    f,
+                    "|{} - {}|{}|{}|{}|",
+                    offer.company,
+                    offer.position,
+                    offer.offer_date,
+                    display_or_na(offer.offer_amt),
+                    if offer.negotiating {
+                        "negotiating"
+                    } else {
+                        "no decision"
+                    }
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::{input_stats_from_csv, validate};
+
+    const CSV: &str = "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,decision_dt,referral,url,notes
+A,Engineer,20220301,,20220306,20220320,100000,accepted,20220324,,,
+B,Engineer,20220301,,20220308,20220322,90000,yes,,,,
+C,Engineer,20220301,,20220308,20220322,95000,declined,20220330,,,
+D,Engineer,20220301,,20220308,20220325,,negotiating,,,,
+E,Engineer,20220301,,20220308,20220326,,rescinded,20220401,,,
+F,Engineer,20220301,,20220308,20220327,,,,,,
+G,Engineer,20220301,,,,,,,,,
+";
+
+    #[test]
+    fn test_decision_stats() {
+        let stats = DecisionStats::new(&input_stats_from_csv(CSV));
+
+        assert_eq!(6, stats.offers);
+        assert_eq!(2, stats.accepted);
+        assert_eq!(1, stats.declined);
+        assert_eq!(1, stats.rescinded);
+        assert_eq!(3, stats.decided_by_me());
+        // rescinded offers were not my decision so their date does not count
+        assert_eq!(Some(6.0), stats.mean_days_offer_to_decision);
+        assert_eq!(
+            vec!["D", "F"],
+            stats
+                .pending
+                .iter()
+                .map(|p| p.company.as_str())
+                .collect::<Vec<&str>>()
+        );
+        assert!(stats.pending[0].negotiating);
+        assert!(stats.to_string().contains("|Offers pending decision|2|"));
+    }
+
+    #[test]
+    fn test_unrecognized_decision_warns() {
+        let input_stats = input_stats_from_csv(
+            "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,decision_dt,referral,url,notes
+A,Engineer,20220301,,20220306,20220320,,maybe,,,,
+B,Engineer,20220301,,20220306,20220320,,accepted,20220319,,,
+C,Engineer,20220301,,,,,,20220319,,,
+",
+        );
+
+        let warnings = validate(&input_stats);
+        assert_eq!(3, warnings.len());
+        assert_eq!("a-engineer-20220301", warnings[0].application_id);
+        assert!(warnings[0].message.contains("unknown offer decision maybe"));
+        assert!(warnings[1].message.contains("before the offer date"));
+        assert!(warnings[2].message.contains("no offer decision"));
+
+        // still counted as an offer that needs a decision
+        assert_eq!(1, DecisionStats::new(&input_stats).pending.len());
+    }
+}
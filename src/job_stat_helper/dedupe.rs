@@ -0,0 +1,310 @@
+use super::aliases::{company_key, position_key, Aliases};
+use super::InputStat;
+
+/// When two applications are likely to be the same one entered twice
+#[derive(Clone, Copy, Debug)]
+pub struct DuplicateConfig {
+    /// most days between the two applied dates
+    pub window_days: i64,
+    /// least Jaro-Winkler similarity between the two positions
+    pub position_similarity: f64,
+}
+
+impl Default for DuplicateConfig {
+    fn default() -> Self {
+        Self {
+            window_days: 14,
+            position_similarity: 0.85,
+        }
+    }
+}
+
+/// Two rows of the input, by index, that are probably the same application
+#[derive(Clone, Debug, PartialEq)]
+pub struct Duplicate {
+    pub first: usize,
+    pub second: usize,
+    pub position_similarity: f64,
+}
+
+/// Pairs of applications to the same company, once `aliases` are applied and compared by
+/// `company_key`, for similar positions within `config.window_days` of each other
+pub fn find_duplicates(
+    input_stats: &[InputStat],
+    aliases: &Aliases,
+    config: &DuplicateConfig,
+) -> Vec<Duplicate> {
+    let normalized = aliases.apply(input_stats);
+
+    let mut duplicates = vec![];
+    for (i, a) in normalized.iter().enumerate() {
+        for (j, b) in normalized.iter().enumerate().skip(i + 1) {
+            if company_key(&a.company) != company_key(&b.company)
+                || (a.applied_date() - b.applied_date()).num_days().abs() > config.window_days
+            {
+                continue;
+            }
+            let position_similarity =
+                strsim::jaro_winkler(&position_key(&a.position), &position_key(&b.position));
+            if position_similarity >= config.position_similarity {
+                duplicates.push(Duplicate {
+                    first: i,
+                    second: j,
+                    position_similarity,
+                });
+            }
+        }
+    }
+
+    duplicates
+}
+
+/// One application out of two entries of it
+/// the earlier applied one is kept with anything it is missing filled in from the other, notes
+/// that differ are both kept. The offer columns are taken together from one row or the other
+pub fn merge(a: &InputStat, b: &InputStat) -> InputStat {
+    let (kept, other) = if b.applied_dt < a.applied_dt {
+        (b, a)
+    } else {
+        (a, b)
+    };
+
+    // the offer columns describe one offer so all of them come from the row that knows more
+    // about it, an amount only means something in its own currency
+    let offer = if offer_details(kept) >= offer_details(other) {
+        kept
+    } else {
+        other
+    };
+    // -1 only records that i declined an interview, a date in the other row says there was one
+    let first_interview = match (kept.first_interview_date(), other.first_interview_date()) {
+        (None, Some(_)) => other.first_interview,
+        _ => kept.first_interview.or(other.first_interview),
+    };
+    let notes = match (&kept.notes, &other.notes) {
+        (Some(k), Some(o)) if k != o => Some(format!("{}; {}", k, o)),
+        (k, o) => k.clone().or_else(|| o.clone()),
+    };
+    InputStat {
+        rejected_dt: kept.rejected_dt.or(other.rejected_dt),
+        first_interview,
+        offer_dt: offer.offer_dt,
+        offer_amt: offer.offer_amt,
+        currency: offer.currency.clone(),
+        accepted: offer.accepted.clone(),
+        decision_dt: offer.decision_dt,
+        referral: kept.referral.clone().or_else(|| other.referral.clone()),
+        url: kept.url.clone().or_else(|| other.url.clone()),
+        notes,
+        owner: kept.owner.clone().or_else(|| other.owner.clone()),
+        resume_version: kept
+            .resume_version
+            .clone()
+            .or_else(|| other.resume_version.clone()),
+        channel: kept.channel.clone().or_else(|| other.channel.clone()),
+        ..kept.clone()
+    }
+}
+
+/// how many of the offer columns of the row are filled in
+fn offer_details(stat: &InputStat) -> usize {
+    [
+        stat.offer_dt.is_some(),
+        stat.offer_amt.is_some(),
+        stat.currency.is_some(),
+        stat.accepted.is_some(),
+        stat.decision_dt.is_some(),
+    ]
+    .into_iter()
+    .filter(|filled| *filled)
+    .count()
+}
+
+/// The rows linked by one or more duplicate pairs, each group is probably one application
+/// so pairs (0, 1), (0, 4) and (1, 4) make the single group [0, 1, 4]
+pub fn group_duplicates(duplicates: &[Duplicate]) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = vec![];
+
+    for duplicate in duplicates {
+        let with_first = groups.iter().position(|g| g.contains(&duplicate.first));
+        let with_second = groups.iter().position(|g| g.contains(&duplicate.second));
+        match (with_first, with_second) {
+            (Some(a), Some(b)) if a == b => {}
+            (Some(a), Some(b)) => {
+                let joined = groups.remove(a.max(b));
+                groups[a.min(b)].extend(joined);
+            }
+            (Some(a), None) => groups[a].push(duplicate.second),
+            (None, Some(b)) => groups[b].push(duplicate.first),
+            (None, None) => groups.push(vec![duplicate.first, duplicate.second]),
+        }
+    }
+
+    for group in &mut groups {
+        group.sort_unstable();
+    }
+    groups.sort();
+    groups
+}
+
+/// The input with the rows of each group merged into one row, in the place of the first of them
+/// the groups must not share rows, as those from `group_duplicates` do not
+pub fn merge_groups(input_stats: &[InputStat], groups: &[Vec<usize>]) -> Vec<InputStat> {
+    let mut rows: Vec<Option<InputStat>> = input_stats.iter().cloned().map(Some).collect();
+
+    for group in groups {
+        let (first, rest) = match group.split_first() {
+            Some(split) => split,
+            None => continue,
+        };
+        let merged = rest.iter().fold(input_stats[*first].clone(), |merged, i| {
+            merge(&merged, &input_stats[*i])
+        });
+        rows[*first] = Some(merged);
+        for i in rest {
+            rows[*i] = None;
+        }
+    }
+
+    rows.into_iter().flatten().collect()
+}
+
+/// The input with each pair of `merges` merged into one row, in the place of the first of them
+/// a row merged more than once ends up with everything merged into it
+pub fn merge_duplicates(input_stats: &[InputStat], merges: &[Duplicate]) -> Vec<InputStat> {
+    merge_groups(input_stats, &group_duplicates(merges))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::input_stats_from_csv;
+
+    const CSV: &str = "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes
+Acme,Backend Engineer,20220401,,,,,,,,first entry
+ACME Inc.,Backend Engineer,20220403,20220420,,,,,,https://acme.example,second entry
+Acme,Sales Manager,20220402,,,,,,,,
+Acme,Backend Engineer,20220601,,,,,,,,
+acme corp,Backend Engineer.,20220404,,20220410,,,,,,
+";
+
+    #[test]
+    fn test_find_duplicates() {
+        let duplicates = find_duplicates(
+            &input_stats_from_csv(CSV),
+            &Aliases::default(),
+            &DuplicateConfig::default(),
+        );
+        let pairs: Vec<(usize, usize)> = duplicates.iter().map(|d| (d.first, d.second)).collect();
+
+        // not the sales role nor the application two months later
+        assert_eq!(vec![(0, 1), (0, 4), (1, 4)], pairs);
+        assert_eq!(1.0, duplicates[0].position_similarity);
+    }
+
+    #[test]
+    fn test_merge_duplicates() {
+        let input_stats = input_stats_from_csv(CSV);
+        let duplicates = find_duplicates(
+            &input_stats,
+            &Aliases::default(),
+            &DuplicateConfig::default(),
+        );
+        let merged = merge_duplicates(&input_stats, &duplicates);
+
+        assert_eq!(3, merged.len());
+        assert_eq!("Acme", merged[0].company);
+        assert_eq!(20220401.0, merged[0].applied_dt);
+        assert_eq!(Some(20220420.0), merged[0].rejected_dt);
+        assert_eq!(Some(20220410.0), merged[0].first_interview);
+        assert_eq!(Some("https://acme.example"), merged[0].url());
+        assert_eq!(Some("first entry; second entry"), merged[0].notes());
+        assert_eq!("Sales Manager", merged[1].position);
+    }
+
+    #[test]
+    fn test_group_duplicates() {
+        let duplicates = find_duplicates(
+            &input_stats_from_csv(CSV),
+            &Aliases::default(),
+            &DuplicateConfig::default(),
+        );
+        assert_eq!(vec![vec![0, 1, 4]], group_duplicates(&duplicates));
+
+        let pair = |first, second| Duplicate {
+            first,
+            second,
+            position_similarity: 1.0,
+        };
+        assert_eq!(
+            vec![vec![0, 2, 3, 5], vec![1, 4]],
+            group_duplicates(&[pair(0, 2), pair(3, 5), pair(1, 4), pair(2, 5)])
+        );
+    }
+
+    #[test]
+    fn test_merge_keeps_offer_amount_with_its_currency() {
+        let input_stats = input_stats_from_csv(
+            "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,currency,accepted,referral,url,notes
+Acme,Engineer,20220401,,,,,EUR,,,,
+Acme,Engineer,20220402,,,20220420,100000,USD,,,,
+",
+        );
+
+        let merged = merge(&input_stats[0], &input_stats[1]);
+        assert_eq!(Some(100000.0), merged.offer_amt);
+        assert_eq!(Some("USD"), merged.currency());
+    }
+
+    #[test]
+    fn test_merge_keeps_offer_date_with_its_amount() {
+        let input_stats = input_stats_from_csv(
+            "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,currency,accepted,referral,url,notes
+Acme,Engineer,20220401,,,20220418,,,,,,
+Acme,Engineer,20220402,,,20220420,100000,USD,,,,
+",
+        );
+
+        let merged = merge(&input_stats[0], &input_stats[1]);
+        assert_eq!(Some(20220420.0), merged.offer_dt);
+        assert_eq!(Some(100000.0), merged.offer_amt);
+    }
+
+    #[test]
+    fn test_merge_keeps_decision_with_its_date() {
+        let input_stats = input_stats_from_csv(
+            "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,currency,accepted,decision_dt,referral,url,notes
+Acme,Engineer,20220401,,,20220420,,,declined,,,,
+Acme,Engineer,20220402,,,20220420,,,accepted,20220425,,,
+",
+        );
+
+        let merged = merge(&input_stats[0], &input_stats[1]);
+        assert_eq!(Some(String::from("accepted")), merged.accepted);
+        assert_eq!(Some(20220425.0), merged.decision_dt);
+    }
+
+    #[test]
+    fn test_merge_prefers_an_interview_date_over_declined() {
+        let input_stats = input_stats_from_csv(
+            "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes
+Acme,Engineer,20220401,,-1,,,,,,
+Acme,Engineer,20220402,,20220408,,,,,,
+",
+        );
+
+        assert_eq!(
+            Some(20220408.0),
+            merge(&input_stats[0], &input_stats[1]).first_interview
+        );
+        assert_eq!(
+            Some(20220408.0),
+            merge(&input_stats[1], &input_stats[0]).first_interview
+        );
+    }
+}
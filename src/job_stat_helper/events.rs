@@ -0,0 +1,677 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::decisions::OfferDecision;
+use super::interviews::InterviewType;
+use super::{datetime_to_f64, serialize_date, try_f64_to_datetime, InputStat};
+
+#[derive(Debug, thiserror::Error)]
+pub enum EventLogError {
+    #[error("failed to read or write the event log: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid event log csv: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("unknown event {0:?} for {1}")]
+    UnknownEvent(String, String),
+    #[error(
+        "unknown interview type {0:?} for {1}, expected phone, technical, onsite or behavioral"
+    )]
+    UnknownInterviewType(String, String),
+    #[error("{0} event for {1} is missing its {2}")]
+    MissingField(&'static str, String, &'static str),
+    #[error("{0} has events but was never applied to")]
+    MissingApplied(String),
+    #[error("unknown offer decision {0:?} for {1}")]
+    UnknownDecision(String, String),
+    #[error("invalid date {1} on line {0} of the event log, expected YYYYMMDD")]
+    InvalidDate(usize, f64),
+    #[error("invalid rating {1} on line {0} of the event log, expected 1 to 5")]
+    InvalidRating(usize, u8),
+}
+
+/// Something that happened to an application
+#[derive(Clone, Debug, PartialEq)]
+pub enum EventKind {
+    Applied {
+        company: String,
+        position: String,
+        url: Option<String>,
+        referral: Option<String>,
+        /// where the application came from, see `channels::Channel`
+        channel: Option<String>,
+        resume_version: Option<String>,
+        /// whose tracker the application belongs to
+        owner: Option<String>,
+    },
+    /// a recruiter or phone screen before the interview rounds
+    Screened,
+    Interviewed {
+        round: u32,
+        interview_type: Option<InterviewType>,
+        /// how i think it went, 1 (badly) to 5 (great)
+        rating: Option<u8>,
+    },
+    Rejected,
+    Offered {
+        amount: Option<f64>,
+        currency: Option<String>,
+    },
+    /// i declined, either the interview process or the offer
+    Declined,
+    Accepted,
+    /// any decision on the offer, including the ones i did not make like a rescinded offer
+    Decided {
+        decision: OfferDecision,
+    },
+    Note {
+        text: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+    pub application_id: String,
+    pub date: NaiveDate,
+    pub kind: EventKind,
+}
+
+/// One line of the event log csv, every column other than the id, date and event is optional
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+struct EventRow {
+    application_id: String,
+    #[serde(serialize_with = "serialize_date")]
+    date: f64,
+    event: String,
+    round: Option<u32>,
+    interview_type: Option<String>,
+    rating: Option<u8>,
+    amount: Option<f64>,
+    currency: Option<String>,
+    decision: Option<String>,
+    company: Option<String>,
+    position: Option<String>,
+    url: Option<String>,
+    referral: Option<String>,
+    channel: Option<String>,
+    resume_version: Option<String>,
+    owner: Option<String>,
+    text: Option<String>,
+}
+
+impl From<&Event> for EventRow {
+    fn from(event: &Event) -> Self {
+        let mut row = EventRow {
+            application_id: event.application_id.clone(),
+            date: datetime_to_f64(event.date),
+            ..Default::default()
+        };
+
+        row.event = match &event.kind {
+            EventKind::Applied {
+                company,
+                position,
+                url,
+                referral,
+                channel,
+                resume_version,
+                owner,
+            } => {
+                row.company = Some(company.clone());
+                row.position = Some(position.clone());
+                row.url = url.clone();
+                row.referral = referral.clone();
+                row.channel = channel.clone();
+                row.resume_version = resume_version.clone();
+                row.owner = owner.clone();
+                "applied"
+            }
+            EventKind::Screened => "screened",
+            EventKind::Interviewed {
+                round,
+                interview_type,
+                rating,
+            } => {
+                row.round = Some(*round);
+                row.interview_type = interview_type.map(|t| t.to_string());
+                row.rating = *rating;
+                "interviewed"
+            }
+            EventKind::Rejected => "rejected",
+            EventKind::Offered { amount, currency } => {
+                row.amount = *amount;
+                row.currency = currency.clone();
+                "offered"
+            }
+            EventKind::Declined => "declined",
+            EventKind::Accepted => "accepted",
+            EventKind::Decided { decision } => {
+                row.decision = Some(decision.to_string());
+                "decided"
+            }
+            EventKind::Note { text } => {
+                row.text = Some(text.clone());
+                "note"
+            }
+        }
+        .to_string();
+
+        row
+    }
+}
+
+impl EventRow {
+    /// `line` is where the row is in the log file, counting the header as line 1
+    fn into_event(self, line: usize) -> Result<Event, EventLogError> {
+        let date =
+            try_f64_to_datetime(self.date).ok_or(EventLogError::InvalidDate(line, self.date))?;
+        let kind = match self.event.to_lowercase().as_str() {
+            "applied" => EventKind::Applied {
+                company: self.company.ok_or_else(|| {
+                    EventLogError::MissingField("applied", self.application_id.clone(), "company")
+                })?,
+                position: self.position.ok_or_else(|| {
+                    EventLogError::MissingField("applied", self.application_id.clone(), "position")
+                })?,
+                url: self.url,
+                referral: self.referral,
+                channel: self.channel,
+                resume_version: self.resume_version,
+                owner: self.owner,
+            },
+            "screened" => EventKind::Screened,
+            "interviewed" => EventKind::Interviewed {
+                round: self.round.unwrap_or(1),
+                interview_type: match self.interview_type {
+                    Some(t) => Some(t.parse().map_err(|_| {
+                        EventLogError::UnknownInterviewType(t.clone(), self.application_id.clone())
+                    })?),
+                    None => None,
+                },
+                rating: match self.rating {
+                    Some(rating) if !(1..=5).contains(&rating) => {
+                        return Err(EventLogError::InvalidRating(line, rating))
+                    }
+                    rating => rating,
+                },
+            },
+            "rejected" => EventKind::Rejected,
+            "offered" => EventKind::Offered {
+                amount: self.amount,
+                currency: self.currency,
+            },
+            "declined" => EventKind::Declined,
+            "accepted" => EventKind::Accepted,
+            "decided" => {
+                let decision = self.decision.ok_or_else(|| {
+                    EventLogError::MissingField("decided", self.application_id.clone(), "decision")
+                })?;
+                EventKind::Decided {
+                    decision: decision.parse().map_err(|_| {
+                        EventLogError::UnknownDecision(
+                            decision.clone(),
+                            self.application_id.clone(),
+                        )
+                    })?,
+                }
+            }
+            "note" => EventKind::Note {
+                text: self.text.unwrap_or_default(),
+            },
+            other => {
+                return Err(EventLogError::UnknownEvent(
+                    other.to_string(),
+                    self.application_id,
+                ))
+            }
+        };
+
+        Ok(Event {
+            application_id: self.application_id,
+            date,
+            kind,
+        })
+    }
+}
+
+/// Reads every event in the log, in the order they were appended
+pub fn read_event_log<R: std::io::Read>(rdr: R) -> Result<Vec<Event>, EventLogError> {
+    csv::Reader::from_reader(rdr)
+        .deserialize::<EventRow>()
+        .enumerate()
+        .map(|(i, row)| row?.into_event(i + 2))
+        .collect()
+}
+
+/// Writes events to a fresh log
+pub fn write_event_log<W: std::io::Write>(wtr: W, events: &[Event]) -> Result<(), EventLogError> {
+    let mut csv_writer = csv::Writer::from_writer(wtr);
+    for event in events {
+        csv_writer.serialize(EventRow::from(event))?;
+    }
+    csv_writer.flush()?;
+
+    Ok(())
+}
+
+/// Appends events to the end of the log file, creating it if needed
+/// a log written before the current columns existed is rewritten with them first
+pub fn append_events(path: &Path, events: &[Event]) -> Result<(), EventLogError> {
+    let existing_header = match std::fs::File::open(path) {
+        Ok(file) => csv::Reader::from_reader(file).headers()?.clone(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => csv::StringRecord::new(),
+        Err(e) => return Err(e.into()),
+    };
+
+    if !existing_header.is_empty() && existing_header != event_log_header() {
+        let mut all_events = read_event_log(std::fs::File::open(path)?)?;
+        all_events.extend_from_slice(events);
+        return write_event_log(std::fs::File::create(path)?, &all_events);
+    }
+
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+
+    let mut csv_writer = csv::WriterBuilder::new()
+        .has_headers(existing_header.is_empty())
+        .from_writer(file);
+    for event in events {
+        csv_writer.serialize(EventRow::from(event))?;
+    }
+    csv_writer.flush()?;
+
+    Ok(())
+}
+
+/// the columns the log is written with
+fn event_log_header() -> csv::StringRecord {
+    // unwraps are okay because the row is only written to and read back from memory
+    let mut csv_writer = csv::Writer::from_writer(vec![]);
+    csv_writer.serialize(EventRow::default()).unwrap();
+    let written = csv_writer.into_inner().unwrap();
+
+    csv::Reader::from_reader(written.as_slice())
+        .headers()
+        .unwrap()
+        .clone()
+}
+
+/// Rebuilds today's one row per application view from the event log
+/// events are applied in date order, the order they were logged breaking ties
+pub fn project(events: &[Event]) -> Result<Vec<InputStat>, EventLogError> {
+    let mut by_application: BTreeMap<&str, Vec<&Event>> = BTreeMap::new();
+    for event in events {
+        by_application
+            .entry(event.application_id.as_str())
+            .or_default()
+            .push(event);
+    }
+
+    let mut input_stats = vec![];
+    for (application_id, mut events) in by_application {
+        events.sort_by_key(|e| e.date);
+
+        let mut stat = match events.iter().find_map(|e| match &e.kind {
+            EventKind::Applied {
+                company,
+                position,
+                url,
+                referral,
+                channel,
+                resume_version,
+                owner,
+            } => Some(InputStat {
+                company: company.clone(),
+                position: position.clone(),
+                applied_dt: datetime_to_f64(e.date),
+                rejected_dt: None,
+                first_interview: None,
+                offer_dt: None,
+                offer_amt: None,
+                currency: None,
+                accepted: None,
+                decision_dt: None,
+                referral: referral.clone(),
+                url: url.clone(),
+                notes: None,
+                owner: owner.clone(),
+                resume_version: resume_version.clone(),
+                channel: channel.clone(),
+            }),
+            _ => None,
+        }) {
+            Some(stat) => stat,
+            None => return Err(EventLogError::MissingApplied(application_id.to_string())),
+        };
+
+        let mut notes = vec![];
+        for event in events {
+            let date = Some(datetime_to_f64(event.date));
+            match &event.kind {
+                EventKind::Applied { .. } => {}
+                EventKind::Screened | EventKind::Interviewed { .. } => {
+                    if stat.first_interview.is_none() {
+                        stat.first_interview = date;
+                    }
+                }
+                EventKind::Rejected => stat.rejected_dt = date,
+                EventKind::Offered { amount, currency } => {
+                    stat.offer_dt = date;
+                    stat.offer_amt = *amount;
+                    stat.currency = currency.clone();
+                }
+                EventKind::Declined if stat.offer_dt.is_some() => {
+                    stat.accepted = Some(OfferDecision::Declined.to_string());
+                    stat.decision_dt = date;
+                }
+                // declining before any interview is recorded the same way as the csv does it
+                EventKind::Declined if stat.first_interview.is_none() => {
+                    stat.first_interview = Some(-1.0)
+                }
+                // withdrawing after interviewing ends the process, the csv has no column for it
+                // other than a rejection
+                EventKind::Declined => {
+                    if stat.rejected_dt.is_none() {
+                        stat.rejected_dt = date;
+                    }
+                }
+                EventKind::Accepted => {
+                    stat.accepted = Some(OfferDecision::Accepted.to_string());
+                    stat.decision_dt = date;
+                }
+                // an offer still being negotiated has not been decided yet so has no date
+                EventKind::Decided { decision } => {
+                    stat.accepted = Some(decision.to_string());
+                    stat.decision_dt = match decision {
+                        OfferDecision::Negotiating => None,
+                        _ => date,
+                    };
+                }
+                EventKind::Note { text } => notes.push(text.clone()),
+            }
+        }
+        if !notes.is_empty() {
+            stat.notes = Some(notes.join("\n"));
+        }
+
+        input_stats.push(stat);
+    }
+
+    input_stats.sort_by(|a, b| a.applied_dt.total_cmp(&b.applied_dt));
+    Ok(input_stats)
+}
+
+/// Turns one row per application into the events that would have produced it
+/// decisions on an offer without a decision date are dated with the offer
+pub fn events_from_input_stats(input_stats: &[InputStat]) -> Vec<Event> {
+    let mut events = vec![];
+
+    for stat in input_stats {
+        let application_id = stat.application_id();
+        let applied = stat.applied_date();
+        let event = |date: NaiveDate, kind: EventKind| Event {
+            application_id: application_id.clone(),
+            date,
+            kind,
+        };
+
+        events.push(event(
+            applied,
+            EventKind::Applied {
+                company: stat.company.clone(),
+                position: stat.position.clone(),
+                url: stat.url.clone(),
+                referral: stat.referral.clone(),
+                channel: stat.channel.clone(),
+                resume_version: stat.resume_version.clone(),
+                owner: stat.owner.clone(),
+            },
+        ));
+        match (stat.first_interview, stat.first_interview_date()) {
+            (_, Some(interview)) => events.push(event(
+                interview,
+                EventKind::Interviewed {
+                    round: 1,
+                    interview_type: None,
+                    rating: None,
+                },
+            )),
+            (Some(_), None) => events.push(event(applied, EventKind::Declined)),
+            (None, None) => {}
+        }
+        if let Some(rejected) = stat.rejected_date() {
+            events.push(event(rejected, EventKind::Rejected));
+        }
+        if let Some(offer) = stat.offer_date() {
+            events.push(event(
+                offer,
+                EventKind::Offered {
+                    amount: stat.offer_amt,
+                    currency: stat.currency.clone(),
+                },
+            ));
+            let decided = stat.decision_date().unwrap_or(offer);
+            // accepting and declining have their own events, any other decision is logged as one
+            match stat.offer_decision() {
+                Some(Ok(OfferDecision::Declined)) => {
+                    events.push(event(decided, EventKind::Declined))
+                }
+                Some(Ok(OfferDecision::Accepted)) => {
+                    events.push(event(decided, EventKind::Accepted))
+                }
+                Some(Ok(decision)) => events.push(event(decided, EventKind::Decided { decision })),
+                Some(Err(_)) | None => {}
+            }
+        }
+        if let Some(notes) = &stat.notes {
+            events.push(event(
+                applied,
+                EventKind::Note {
+                    text: notes.clone(),
+                },
+            ));
+        }
+    }
+
+    events
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::input_stats_from_csv;
+
+    const LOG: &str = "\
+application_id,date,event,round,interview_type,rating,amount,currency,company,position,url,referral,text
+acme-1,20220401,applied,,,,,,Acme,Engineer,https://acme.example,,
+acme-1,20220405,screened,,,,,,,,,,
+acme-1,20220410,interviewed,1,phone,4,,,,,,,
+acme-1,20220412,note,,,,,,,,,,went well
+acme-1,20220415,interviewed,2,onsite,,,,,,,,
+acme-1,20220420,offered,,,,120000,EUR,,,,,
+acme-1,20220422,accepted,,,,,,,,,,
+globex-1,20220402,applied,,,,,,Globex,Developer,,yes,
+globex-1,20220403,declined,,,,,,,,,,
+";
+
+    #[test]
+    fn test_project_event_log() {
+        let events = read_event_log(LOG.as_bytes()).unwrap();
+        assert_eq!(9, events.len());
+        assert_eq!(
+            EventKind::Interviewed {
+                round: 2,
+                interview_type: Some(InterviewType::Onsite),
+                rating: None
+            },
+            events[4].kind
+        );
+
+        let input_stats = project(&events).unwrap();
+        assert_eq!(2, input_stats.len());
+
+        let acme = &input_stats[0];
+        assert_eq!(Some(20220405.0), acme.first_interview);
+        assert_eq!(Some(20220420.0), acme.offer_dt);
+        assert_eq!(Some(120000.0), acme.offer_amt);
+        assert_eq!(Some(String::from("EUR")), acme.currency);
+        assert_eq!(Some(String::from("accepted")), acme.accepted);
+        assert_eq!(Some(String::from("went well")), acme.notes);
+
+        let globex = &input_stats[1];
+        assert_eq!(Some(-1.0), globex.first_interview);
+        assert_eq!(Some(String::from("yes")), globex.referral);
+    }
+
+    #[test]
+    fn test_unknown_event_and_missing_applied() {
+        let log = "application_id,date,event,round,interview_type\n\
+                   a,20220401,ghosted,,\n";
+        assert!(matches!(
+            read_event_log(log.as_bytes()),
+            Err(EventLogError::UnknownEvent(..))
+        ));
+
+        let log = "application_id,date,event,round,interview_type\n\
+                   a,20220401,interviewed,1,coffee\n";
+        assert!(matches!(
+            read_event_log(log.as_bytes()),
+            Err(EventLogError::UnknownInterviewType(..))
+        ));
+
+        let log = "application_id,date,event,round,interview_type\n\
+                   a,20220401,screened,,\n\
+                   a,20220231,rejected,,\n";
+        assert!(matches!(
+            read_event_log(log.as_bytes()),
+            Err(EventLogError::InvalidDate(3, date)) if date == 20220231.0
+        ));
+
+        let log = "application_id,date,event,round,interview_type,rating\n\
+                   a,20220401,interviewed,1,phone,4\n\
+                   a,20220402,interviewed,2,phone,9\n";
+        assert!(matches!(
+            read_event_log(log.as_bytes()),
+            Err(EventLogError::InvalidRating(3, 9))
+        ));
+
+        let events = vec![Event {
+            application_id: String::from("a"),
+            date: NaiveDate::from_ymd_opt(2022, 4, 1).unwrap(),
+            kind: EventKind::Rejected,
+        }];
+        assert!(matches!(
+            project(&events),
+            Err(EventLogError::MissingApplied(..))
+        ));
+    }
+
+    #[test]
+    fn test_round_trip_through_events() {
+        let csv = "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,currency,accepted,decision_dt,referral,url,notes,owner,resume_version,channel
+Acme,Engineer,20220401,,20220405,20220420,100000,EUR,declined,20220425,yes,https://acme.example,\"a, note\",alice,v2,referral
+Globex,Developer,20220402,20220410,-1,,,,,,,,,bob,,job-board
+Initech,Engineer,20220403,20220415,,,,,,,,,,,v1,
+Hooli,Engineer,20220404,,20220406,20220420,90000,,expired,20220501,,,,,,
+Umbrella,Engineer,20220405,,20220407,20220421,80000,,rescinded,20220425,,,,,,
+Vandelay,Engineer,20220406,,20220408,20220422,70000,,negotiating,,,,,,,
+";
+        let input_stats = input_stats_from_csv(csv);
+
+        let mut log = vec![];
+        write_event_log(&mut log, &events_from_input_stats(&input_stats)).unwrap();
+        let projected = project(&read_event_log(log.as_slice()).unwrap()).unwrap();
+
+        assert_eq!(to_csv(&input_stats), to_csv(&projected));
+    }
+
+    #[test]
+    fn test_withdrawing_and_decided() {
+        let log = "\
+application_id,date,event,decision,company,position
+acme-1,20220401,applied,,Acme,Engineer
+acme-1,20220405,interviewed,,,
+acme-1,20220408,declined,,,
+globex-1,20220402,applied,,Globex,Engineer
+globex-1,20220410,offered,,,
+globex-1,20220415,decided,rescinded,,
+";
+        let events = read_event_log(log.as_bytes()).unwrap();
+        assert_eq!(
+            EventKind::Decided {
+                decision: OfferDecision::Rescinded
+            },
+            events[5].kind
+        );
+
+        let input_stats = project(&events).unwrap();
+        // withdrawing after the interview closes the application
+        assert_eq!(Some(20220408.0), input_stats[0].rejected_dt);
+        assert_eq!(None, input_stats[0].accepted);
+        assert_eq!(Some(String::from("rescinded")), input_stats[1].accepted);
+        assert_eq!(Some(20220415.0), input_stats[1].decision_dt);
+
+        let log = "application_id,date,event,decision\n\
+                   a,20220401,decided,maybe\n";
+        assert!(matches!(
+            read_event_log(log.as_bytes()),
+            Err(EventLogError::UnknownDecision(..))
+        ));
+    }
+
+    fn to_csv(input_stats: &[InputStat]) -> String {
+        let mut out = csv::Writer::from_writer(vec![]);
+        for stat in input_stats {
+            out.serialize(stat).unwrap();
+        }
+        String::from_utf8(out.into_inner().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_append_events() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.csv");
+        let events = read_event_log(LOG.as_bytes()).unwrap();
+
+        append_events(&path, &events[..3]).unwrap();
+        append_events(&path, &events[3..]).unwrap();
+
+        let file = std::fs::File::open(&path).unwrap();
+        assert_eq!(events, read_event_log(file).unwrap());
+    }
+
+    #[test]
+    fn test_append_events_to_older_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.csv");
+        // written before interview types, ratings and currencies were logged
+        std::fs::write(
+            &path,
+            "\
+application_id,date,event,round,amount,company,position,url,referral,text
+acme-1,20220401,applied,,,Acme,Engineer,,,
+acme-1,20220410,interviewed,1,,,,,,
+",
+        )
+        .unwrap();
+        let events = read_event_log(LOG.as_bytes()).unwrap();
+
+        append_events(&path, &events[4..6]).unwrap();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            &event_log_header(),
+            csv::Reader::from_reader(written.as_bytes())
+                .headers()
+                .unwrap()
+        );
+        let appended = read_event_log(written.as_bytes()).unwrap();
+        assert_eq!(4, appended.len());
+        assert_eq!(events[4..6], appended[2..]);
+    }
+}
@@ -0,0 +1,268 @@
+use chrono::NaiveDate;
+
+use super::ical;
+use super::InputStat;
+
+/// How many days to wait before an application or interview needs a follow up
+#[derive(Clone, Copy, Debug)]
+pub struct FollowupConfig {
+    /// days after applying with no rejection or interview
+    pub application_days: i64,
+    /// days after the first interview with no rejection or offer
+    pub interview_days: i64,
+}
+
+impl Default for FollowupConfig {
+    fn default() -> Self {
+        Self {
+            application_days: 14,
+            interview_days: 7,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FollowupKind {
+    /// applied and heard nothing back
+    NoResponse,
+    /// interviewed and heard nothing back
+    NoInterviewOutcome,
+}
+
+#[derive(Clone, Debug)]
+pub struct Followup {
+    pub application_id: String,
+    pub company: String,
+    pub position: String,
+    pub url: Option<String>,
+    pub kind: FollowupKind,
+    /// the date of the last thing that happened (application or first interview)
+    pub since: NaiveDate,
+    pub days_waiting: i64,
+    /// how many days past the configured threshold this is
+    pub days_overdue: i64,
+}
+
+impl Followup {
+    /// the date the follow up became due
+    pub fn due(&self) -> NaiveDate {
+        self.since + chrono::Duration::days(self.days_waiting - self.days_overdue)
+    }
+
+    fn summary(&self) -> String {
+        match self.kind {
+            FollowupKind::NoResponse => format!(
+                "Follow up with {} about the {} application",
+                self.company, self.position
+            ),
+            FollowupKind::NoInterviewOutcome => format!(
+                "Follow up with {} about the {} interview",
+                self.company, self.position
+            ),
+        }
+    }
+
+    /// todo.txt priority, anything a week or more overdue is most urgent
+    fn priority(&self) -> char {
+        match self.days_overdue {
+            d if d >= 7 => 'A',
+            d if d >= 3 => 'B',
+            _ => 'C',
+        }
+    }
+}
+
+/// Finds every open application or interview that has gone quiet for longer than the configured
+/// number of days, most overdue first
+pub fn followups(
+    input_stats: &[InputStat],
+    today: NaiveDate,
+    config: &FollowupConfig,
+) -> Vec<Followup> {
+    let mut followups = vec![];
+
+    for stat in input_stats {
+        // anything rejected, offered or with an interview i declined is closed
+        if stat.rejected_dt.is_some() || stat.offer_dt.is_some() {
+            continue;
+        }
+
+        let (kind, since, threshold) = match (stat.first_interview, stat.first_interview_date()) {
+            (None, _) => (
+                FollowupKind::NoResponse,
+                stat.applied_date(),
+                config.application_days,
+            ),
+            (Some(_), Some(interview)) => (
+                FollowupKind::NoInterviewOutcome,
+                interview,
+                config.interview_days,
+            ),
+            (Some(_), None) => continue,
+        };
+
+        let days_waiting = (today - since).num_days();
+        if days_waiting < threshold {
+            continue;
+        }
+
+        followups.push(Followup {
+            application_id: stat.application_id(),
+            company: stat.company.clone(),
+            position: stat.position.clone(),
+            url: stat.url.clone(),
+            kind,
+            since,
+            days_waiting,
+            days_overdue: days_waiting - threshold,
+        });
+    }
+
+    followups.sort_by(|a, b| {
+        b.days_overdue
+            .cmp(&a.days_overdue)
+            .then(b.days_waiting.cmp(&a.days_waiting))
+            .then(a.company.cmp(&b.company))
+    });
+
+    followups
+}
+
+/// Renders follow ups as a markdown table for the terminal
+pub fn to_table(followups: &[Followup]) -> String {
+    let mut out = String::from("|Company|Position|Waiting on|Since|Days waiting|Days overdue|\n");
+    out.push_str("|---|---|---|---|---|---|\n");
+    for followup in followups {
+        out.push_str(&format!(
+            "|{}|{}|{}|{}|{}|{}|\n",
+            followup.company,
+            followup.position,
+            match followup.kind {
+                FollowupKind::NoResponse => "response",
+                FollowupKind::NoInterviewOutcome => "interview outcome",
+            },
+            followup.since,
+            followup.days_waiting,
+            followup.days_overdue
+        ));
+    }
+
+    out
+}
+
+/// Renders follow ups as todo.txt lines
+/// e.g. `(A) 2022-04-20 Follow up with Acme about the Engineer application +jobsearch due:2022-04-15`
+pub fn to_todo_txt(followups: &[Followup], today: NaiveDate) -> String {
+    let mut out = String::new();
+    for followup in followups {
+        out.push_str(&format!(
+            "({}) {} {} +jobsearch @{} due:{}",
+            followup.priority(),
+            today.format("%Y-%m-%d"),
+            followup.summary(),
+            followup.application_id,
+            followup.due().format("%Y-%m-%d")
+        ));
+        if let Some(url) = &followup.url {
+            out.push_str(&format!(" url:{}", url));
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+/// Renders follow ups as an iCalendar file of VTODOs
+pub fn to_vtodo(followups: &[Followup], today: NaiveDate) -> String {
+    let components = followups
+        .iter()
+        .map(|followup| {
+            let mut vtodo = String::new();
+            ical::push_line(&mut vtodo, "BEGIN:VTODO");
+            ical::push_line(
+                &mut vtodo,
+                &format!("UID:followup-{}@job_stats", followup.application_id),
+            );
+            ical::push_line(
+                &mut vtodo,
+                &format!("DTSTAMP:{}", ical::date_time_value(today)),
+            );
+            ical::push_line(
+                &mut vtodo,
+                &format!("SUMMARY:{}", ical::escape_text(&followup.summary())),
+            );
+            if let Some(url) = &followup.url {
+                ical::push_line(
+                    &mut vtodo,
+                    &format!("DESCRIPTION:{}", ical::escape_text(url)),
+                );
+            }
+            ical::push_line(
+                &mut vtodo,
+                &format!("DUE;VALUE=DATE:{}", ical::date_value(followup.due())),
+            );
+            // iCalendar priorities run 1 (highest) to 9 (lowest)
+            let priority = match followup.priority() {
+                'A' => 1,
+                'B' => 5,
+                _ => 9,
+            };
+            ical::push_line(&mut vtodo, &format!("PRIORITY:{}", priority));
+            ical::push_line(&mut vtodo, "STATUS:NEEDS-ACTION");
+            ical::push_line(&mut vtodo, "END:VTODO");
+            vtodo
+        })
+        .collect::<Vec<String>>();
+
+    ical::calendar(&components)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::input_stats_from_csv;
+
+    const CSV: &str = "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes
+Acme,Engineer,20220401,,,,,,,https://acme.example/jobs/1,
+Globex,Developer,20220301,,,,,,,,
+Initech,Engineer,20220401,,20220410,,,,,,
+Hooli,Engineer,20220401,20220405,,,,,,,
+Umbrella,Engineer,20220401,,-1,,,,,,
+Wayne,Engineer,20220418,,,,,,,,
+";
+
+    #[test]
+    fn test_followups_sorted_by_urgency() {
+        let input_stats = input_stats_from_csv(CSV);
+        let today = NaiveDate::from_ymd_opt(2022, 4, 20).unwrap();
+        let found = followups(&input_stats, today, &FollowupConfig::default());
+
+        let companies: Vec<&str> = found.iter().map(|f| f.company.as_str()).collect();
+        assert_eq!(vec!["Globex", "Acme", "Initech"], companies);
+        assert_eq!(FollowupKind::NoInterviewOutcome, found[2].kind);
+        assert_eq!(3, found[2].days_overdue);
+        assert_eq!(
+            NaiveDate::from_ymd_opt(2022, 4, 15).unwrap(),
+            found[1].due()
+        );
+    }
+
+    #[test]
+    fn test_todo_txt_and_vtodo() {
+        let input_stats = input_stats_from_csv(CSV);
+        let today = NaiveDate::from_ymd_opt(2022, 4, 20).unwrap();
+        let found = followups(&input_stats, today, &FollowupConfig::default());
+
+        let todo = to_todo_txt(&found[1..2], today);
+        assert_eq!(
+            "(B) 2022-04-20 Follow up with Acme about the Engineer application +jobsearch \
+             @acme-engineer-20220401 due:2022-04-15 url:https://acme.example/jobs/1\n",
+            todo
+        );
+
+        let ics = to_vtodo(&found, today);
+        assert_eq!(3, ics.matches("BEGIN:VTODO").count());
+        assert!(ics.contains("DUE;VALUE=DATE:20220415\r\n"));
+    }
+}
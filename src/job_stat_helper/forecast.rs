@@ -0,0 +1,388 @@
+use chrono::NaiveDate;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use super::InputStat;
+
+/// Percentiles reported for each week of the forecast
+pub const PERCENTILES: [f64; 3] = [0.1, 0.5, 0.9];
+
+#[derive(Clone, Copy, Debug)]
+pub struct ForecastConfig {
+    pub weeks: u32,
+    pub runs: u32,
+    pub seed: u64,
+}
+
+impl Default for ForecastConfig {
+    fn default() -> Self {
+        Self {
+            weeks: 8,
+            runs: 10_000,
+            seed: 0,
+        }
+    }
+}
+
+/// Stage conversion rates and durations learned from closed (rejected or offered) applications
+#[derive(Clone, Debug, Default)]
+pub struct ConversionHistory {
+    pub closed: u32,
+    /// share of closed applications that got a first interview
+    pub interview_rate: f64,
+    /// share of closed applications with a first interview that got an offer
+    pub offer_rate: f64,
+    days_to_interview: Vec<i64>,
+    days_interview_to_offer: Vec<i64>,
+}
+
+impl ConversionHistory {
+    pub fn new(input_stats: &[InputStat]) -> Self {
+        let closed: Vec<&InputStat> = input_stats
+            .iter()
+            .filter(|s| s.rejected_dt.is_some() || s.offer_dt.is_some())
+            .collect();
+
+        let mut history = Self {
+            closed: closed.len() as u32,
+            ..Default::default()
+        };
+
+        let mut interviewed = 0;
+        let mut offered = 0;
+        for stat in &closed {
+            let interview = match stat.first_interview_date() {
+                Some(i) => i,
+                None => continue,
+            };
+            interviewed += 1;
+            history
+                .days_to_interview
+                .push((interview - stat.applied_date()).num_days());
+
+            if let Some(offer) = stat.offer_date() {
+                offered += 1;
+                history
+                    .days_interview_to_offer
+                    .push((offer - interview).num_days());
+            }
+        }
+
+        if !closed.is_empty() {
+            history.interview_rate = interviewed as f64 / closed.len() as f64;
+        }
+        if interviewed > 0 {
+            history.offer_rate = offered as f64 / interviewed as f64;
+        }
+
+        history
+    }
+}
+
+/// Where an open application stands today
+#[derive(Clone, Copy, Debug)]
+enum OpenStage {
+    /// waiting on a response since the application date
+    Applied(NaiveDate),
+    /// waiting on an outcome since the first interview
+    Interviewed(NaiveDate),
+}
+
+/// Cumulative interviews and offers expected by the end of one forecast week
+#[derive(Clone, Debug)]
+pub struct ForecastWeek {
+    pub week: u32,
+    pub end: NaiveDate,
+    pub mean_interviews: f64,
+    pub mean_offers: f64,
+    /// interviews at each of `PERCENTILES`
+    pub interviews: Vec<u32>,
+    /// offers at each of `PERCENTILES`
+    pub offers: Vec<u32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Forecast {
+    pub today: NaiveDate,
+    pub open_applications: u32,
+    pub history: ConversionHistory,
+    pub config: ForecastConfig,
+    pub weeks: Vec<ForecastWeek>,
+}
+
+impl Forecast {
+    /// Monte Carlo forecast of the interviews and offers the open applications will turn into
+    /// over the next `config.weeks` weeks, reproducible for a given seed
+    pub fn new(input_stats: &[InputStat], today: NaiveDate, config: ForecastConfig) -> Self {
+        let history = ConversionHistory::new(input_stats);
+        let open = open_stages(input_stats);
+        let mut rng = StdRng::seed_from_u64(config.seed);
+
+        let weeks = config.weeks as usize;
+        // per week, the cumulative count seen in each run
+        let mut interview_runs = vec![Vec::with_capacity(config.runs as usize); weeks];
+        let mut offer_runs = vec![Vec::with_capacity(config.runs as usize); weeks];
+
+        for _ in 0..config.runs {
+            let mut interviews_per_week = vec![0u32; weeks];
+            let mut offers_per_week = vec![0u32; weeks];
+
+            for stage in &open {
+                let (interview, offer) = simulate(*stage, today, &history, &mut rng);
+                if let Some(week) = interview.and_then(|d| week_index(today, d, weeks)) {
+                    interviews_per_week[week] += 1;
+                }
+                if let Some(week) = offer.and_then(|d| week_index(today, d, weeks)) {
+                    offers_per_week[week] += 1;
+                }
+            }
+
+            let (mut interviews, mut offers) = (0, 0);
+            for week in 0..weeks {
+                interviews += interviews_per_week[week];
+                offers += offers_per_week[week];
+                interview_runs[week].push(interviews);
+                offer_runs[week].push(offers);
+            }
+        }
+
+        let weeks = interview_runs
+            .iter_mut()
+            .zip(offer_runs.iter_mut())
+            .enumerate()
+            .map(|(week, (interviews, offers))| ForecastWeek {
+                week: week as u32 + 1,
+                end: today + chrono::Duration::weeks(week as i64 + 1),
+                mean_interviews: mean_u32(interviews),
+                mean_offers: mean_u32(offers),
+                interviews: percentiles(interviews),
+                offers: percentiles(offers),
+            })
+            .collect();
+
+        Self {
+            today,
+            open_applications: open.len() as u32,
+            history,
+            config,
+            weeks,
+        }
+    }
+}
+
+fn open_stages(input_stats: &[InputStat]) -> Vec<OpenStage> {
+    input_stats
+        .iter()
+        .filter(|s| s.rejected_dt.is_none() && s.offer_dt.is_none())
+        .filter_map(|s| match (s.first_interview, s.first_interview_date()) {
+            (None, _) => Some(OpenStage::Applied(s.applied_date())),
+            (Some(_), Some(interview)) => Some(OpenStage::Interviewed(interview)),
+            // an interview i declined is not going anywhere
+            (Some(_), None) => None,
+        })
+        .collect()
+}
+
+/// Simulates one open application, returning the dates of any future interview and offer
+fn simulate(
+    stage: OpenStage,
+    today: NaiveDate,
+    history: &ConversionHistory,
+    rng: &mut StdRng,
+) -> (Option<NaiveDate>, Option<NaiveDate>) {
+    let (interview, waiting_since) = match stage {
+        OpenStage::Interviewed(interview) => (None, interview),
+        OpenStage::Applied(applied) => {
+            let waited = (today - applied).num_days();
+            match next_event(
+                history.interview_rate,
+                &history.days_to_interview,
+                waited,
+                rng,
+            ) {
+                Some(days) => {
+                    let interview = applied + chrono::Duration::days(days);
+                    (Some(interview), interview)
+                }
+                None => return (None, None),
+            }
+        }
+    };
+
+    // an interview simulated in the future has not been waited on at all yet
+    let waited = (today - waiting_since).num_days().max(0);
+    let offer = next_event(
+        history.offer_rate,
+        &history.days_interview_to_offer,
+        waited,
+        rng,
+    )
+    .map(|days| waiting_since + chrono::Duration::days(days));
+
+    (interview, offer)
+}
+
+/// Draws whether an event with historical `rate` and `durations` happens given nothing has
+/// happened in the `waited` days so far, and if so after how many days
+fn next_event(rate: f64, durations: &[i64], waited: i64, rng: &mut StdRng) -> Option<i64> {
+    let remaining: Vec<i64> = durations.iter().copied().filter(|d| *d > waited).collect();
+    if durations.is_empty() || remaining.is_empty() {
+        return None;
+    }
+
+    // P(event | nothing after waited days) = rate * S(waited) / (rate * S(waited) + 1 - rate)
+    let survival = remaining.len() as f64 / durations.len() as f64;
+    let still_possible = rate * survival;
+    let probability = still_possible / (still_possible + 1.0 - rate);
+
+    if rng.gen::<f64>() < probability {
+        remaining.choose(rng).copied()
+    } else {
+        None
+    }
+}
+
+/// which forecast week (0 based) a date falls in, `None` if it is before today or past the horizon
+fn week_index(today: NaiveDate, date: NaiveDate, weeks: usize) -> Option<usize> {
+    let days = (date - today).num_days();
+    if days <= 0 {
+        return None;
+    }
+    let week = ((days - 1) / 7) as usize;
+    (week < weeks).then_some(week)
+}
+
+fn mean_u32(v: &[u32]) -> f64 {
+    if v.is_empty() {
+        return 0.0;
+    }
+    v.iter().map(|x| *x as f64).sum::<f64>() / v.len() as f64
+}
+
+/// nearest rank percentiles of the values at each of `PERCENTILES`
+fn percentiles(v: &mut [u32]) -> Vec<u32> {
+    v.sort_unstable();
+    PERCENTILES
+        .iter()
+        .map(|p| {
+            if v.is_empty() {
+                return 0;
+            }
+            let rank = ((p * v.len() as f64).ceil() as usize).clamp(1, v.len());
+            v[rank - 1]
+        })
+        .collect()
+}
+
+impl std::fmt::Display for Forecast {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        writeln!(f, "|Forecast input|Value|")?;
+        writeln!(f, "|---|---|")?;
+        writeln!(f, "|Open applications|{}|", self.open_applications)?;
+        writeln!(
+            f,
+            "|Closed applications learned from|{}|",
+            self.history.closed
+        )?;
+        writeln!(
+            f,
+            "|Interview rate|{:.2}%|",
+            self.history.interview_rate * 100.0
+        )?;
+        writeln!(
+            f,
+            "|Offer rate after interview|{:.2}%|",
+            self.history.offer_rate * 100.0
+        )?;
+        writeln!(
+            f,
+            "|Simulation runs (seed)|{} ({})|",
+            self.config.runs, self.config.seed
+        )?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "|Week|Ending|Interviews mean|Interviews p10/p50/p90|Offers mean|Offers p10/p50/p90|"
+        )?;
+        writeln!(f, "|---|---|---|---|---|---|")?;
+        for week in &self.weeks {
+            writeln!(
+                f,
+                "|{}|{}|{:.2}|{}|{:.2}|{}|",
+                week.week,
+                week.end,
+                week.mean_interviews,
+                join_percentiles(&week.interviews),
+                week.mean_offers,
+                join_percentiles(&week.offers)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn join_percentiles(v: &[u32]) -> String {
+    v.iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::input_stats_from_csv;
+
+    const CSV: &str = "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes
+A,Engineer,20220301,,20220306,20220320,,,,,
+B,Engineer,20220301,20220320,20220308,,,,,,
+C,Engineer,20220301,20220310,,,,,,,
+D,Engineer,20220301,20220310,,,,,,,
+E,Engineer,20220428,,,,,,,,
+F,Engineer,20220425,,20220429,,,,,,
+G,Engineer,20220425,,-1,,,,,,
+";
+
+    #[test]
+    fn test_conversion_history() {
+        let history = ConversionHistory::new(&input_stats_from_csv(CSV));
+        assert_eq!(4, history.closed);
+        assert_eq!(0.5, history.interview_rate);
+        assert_eq!(0.5, history.offer_rate);
+        assert_eq!(vec![5, 7], history.days_to_interview);
+        assert_eq!(vec![14], history.days_interview_to_offer);
+    }
+
+    #[test]
+    fn test_forecast_is_reproducible_and_bounded() {
+        let input_stats = input_stats_from_csv(CSV);
+        let today = NaiveDate::from_ymd_opt(2022, 4, 30).unwrap();
+        let config = ForecastConfig {
+            weeks: 4,
+            runs: 2_000,
+            seed: 42,
+        };
+
+        let forecast = Forecast::new(&input_stats, today, config);
+        let again = Forecast::new(&input_stats, today, config);
+        assert_eq!(2, forecast.open_applications);
+        assert_eq!(4, forecast.weeks.len());
+
+        for (week, same_week) in forecast.weeks.iter().zip(&again.weeks) {
+            assert_eq!(week.mean_offers, same_week.mean_offers);
+            // only E can still get an interview and at most E and F can get offers
+            assert!(week.interviews.iter().all(|i| *i <= 1));
+            assert!(week.offers.iter().all(|o| *o <= 2));
+        }
+
+        // E waited 2 days and both historical interviews came after 5+ days so half should
+        // interview within the first week
+        assert!((forecast.weeks[0].mean_interviews - 0.5).abs() < 0.05);
+        // F was interviewed yesterday and offers come 14 days after an interview
+        assert_eq!(0.0, forecast.weeks[0].mean_offers);
+        assert!(forecast.weeks[2].mean_offers > 0.4);
+    }
+}
@@ -0,0 +1,278 @@
+use std::path::Path;
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use super::InputStat;
+
+#[derive(Debug, thiserror::Error)]
+pub enum GoalsError {
+    #[error("failed to read the goals file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the goals file is not valid yaml: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+}
+
+/// What i aim to do every week, a goal of 0 is always met
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoalsConfig {
+    #[serde(default)]
+    pub applications: u32,
+    #[serde(default)]
+    pub referrals: u32,
+}
+
+/// Reads goals like `applications: 10` and `referrals: 2`, a missing goal is 0
+pub fn load_goals(path: &Path) -> Result<GoalsConfig, GoalsError> {
+    Ok(serde_yaml::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+/// the monday of the ISO week the date falls in
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - chrono::Duration::days(date.weekday().num_days_from_monday().into())
+}
+
+/// Applications and referrals in one ISO week against the goals
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WeekProgress {
+    pub start: NaiveDate,
+    pub applications: u32,
+    pub referrals: u32,
+}
+
+impl WeekProgress {
+    pub fn label(&self) -> String {
+        let week = self.start.iso_week();
+        format!("{}-W{:02}", week.year(), week.week())
+    }
+
+    pub fn met(&self, goals: &GoalsConfig) -> bool {
+        self.applications >= goals.applications && self.referrals >= goals.referrals
+    }
+
+    /// how many applications and referrals short of the goals the week was
+    pub fn shortfall(&self, goals: &GoalsConfig) -> (u32, u32) {
+        (
+            goals.applications.saturating_sub(self.applications),
+            goals.referrals.saturating_sub(self.referrals),
+        )
+    }
+}
+
+/// Every week from the first application up to and including the current one
+#[derive(Clone, Debug)]
+pub struct GoalProgress {
+    pub goals: GoalsConfig,
+    pub today: NaiveDate,
+    /// oldest first, the last week is the current one
+    pub weeks: Vec<WeekProgress>,
+}
+
+impl GoalProgress {
+    pub fn new(input_stats: &[InputStat], goals: GoalsConfig, today: NaiveDate) -> Self {
+        let current = week_start(today);
+        let first = input_stats
+            .iter()
+            .map(|s| week_start(s.applied_date()))
+            .min()
+            .unwrap_or(current)
+            .min(current);
+
+        let mut weeks: Vec<WeekProgress> = first
+            .iter_weeks()
+            .take_while(|start| *start <= current)
+            .map(|start| WeekProgress {
+                start,
+                applications: 0,
+                referrals: 0,
+            })
+            .collect();
+
+        for stat in input_stats {
+            let applied = stat.applied_date();
+            if applied > today {
+                continue;
+            }
+            let index = ((week_start(applied) - first).num_days() / 7) as usize;
+            weeks[index].applications += 1;
+            if stat.is_referral() {
+                weeks[index].referrals += 1;
+            }
+        }
+
+        Self {
+            goals,
+            today,
+            weeks,
+        }
+    }
+
+    /// weeks that are over, the current week is still in progress
+    fn completed(&self) -> &[WeekProgress] {
+        &self.weeks[..self.weeks.len().saturating_sub(1)]
+    }
+
+    pub fn current_week(&self) -> Option<&WeekProgress> {
+        self.weeks.last()
+    }
+
+    /// Consecutive completed weeks up to last week that met the goals
+    /// the current week extends the streak once it has met them
+    pub fn current_streak(&self) -> u32 {
+        let completed = self
+            .completed()
+            .iter()
+            .rev()
+            .take_while(|w| w.met(&self.goals))
+            .count() as u32;
+        let current = self.current_week().map_or(false, |w| w.met(&self.goals));
+
+        completed + u32::from(current)
+    }
+
+    pub fn longest_streak(&self) -> u32 {
+        let mut longest = 0;
+        let mut streak = 0;
+        for week in &self.weeks {
+            if week.met(&self.goals) {
+                streak += 1;
+                longest = longest.max(streak);
+            } else {
+                streak = 0;
+            }
+        }
+        longest
+    }
+
+    /// Applications and referrals the current week will end with at the pace so far
+    pub fn projection(&self) -> Option<(f64, f64)> {
+        let week = self.current_week()?;
+        let days_so_far = (self.today - week.start).num_days() + 1;
+        let pace = 7.0 / days_so_far as f64;
+
+        Some((
+            week.applications as f64 * pace,
+            week.referrals as f64 * pace,
+        ))
+    }
+}
+
+impl std::fmt::Display for GoalProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        writeln!(f, "|Weekly goals|Value|")?;
+        writeln!(f, "|---|---|")?;
+        writeln!(
+            f,
+            "|Goal per week|{} applications, {} referrals|",
+            self.goals.applications, self.goals.referrals
+        )?;
+        let completed = self.completed();
+        writeln!(
+            f,
+            "|Completed weeks that met the goals|{} of {}|",
+            completed.iter().filter(|w| w.met(&self.goals)).count(),
+            completed.len()
+        )?;
+        writeln!(f, "|Current streak (weeks)|{}|", self.current_streak())?;
+        writeln!(f, "|Longest streak (weeks)|{}|", self.longest_streak())?;
+        if let (Some(week), Some((applications, referrals))) =
+            (self.current_week(), self.projection())
+        {
+            writeln!(
+                f,
+                "|Projected for {}|{:.1} applications, {:.1} referrals ({})|",
+                week.label(),
+                applications,
+                referrals,
+                if applications >= self.goals.applications as f64
+                    && referrals >= self.goals.referrals as f64
+                {
+                    "on track"
+                } else {
+                    "behind"
+                }
+            )?;
+        }
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "|Week|Applications|Referrals|Met goals|Applications short|Referrals short|"
+        )?;
+        writeln!(f, "|---|---|---|---|---|---|")?;
+        for week in &self.weeks {
+            let (applications_short, referrals_short) = week.shortfall(&self.goals);
+            writeln!(
+                f,
+                "|{}|{}|{}|{}|{}|{}|",
+                week.label(),
+                week.applications,
+                week.referrals,
+                if week.met(&self.goals) { "yes" } else { "no" },
+                applications_short,
+                referrals_short
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::input_stats_from_csv;
+
+    const CSV: &str = "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes
+A,Engineer,20220404,,,,,,yes,,
+B,Engineer,20220405,,,,,,,,
+C,Engineer,20220411,,,,,,yes,,
+D,Engineer,20220412,,,,,,,,
+E,Engineer,20220425,,,,,,yes,,
+F,Engineer,20220426,,,,,,,,
+G,Engineer,20220503,,,,,,,,
+";
+
+    #[test]
+    fn test_goal_progress() {
+        let goals = GoalsConfig {
+            applications: 2,
+            referrals: 1,
+        };
+        // a wednesday
+        let today = NaiveDate::from_ymd_opt(2022, 5, 4).unwrap();
+        let progress = GoalProgress::new(&input_stats_from_csv(CSV), goals, today);
+
+        assert_eq!(5, progress.weeks.len());
+        // nothing was sent in the week of the 18th
+        assert_eq!(0, progress.weeks[2].applications);
+        assert_eq!((2, 1), progress.weeks[2].shortfall(&goals));
+        assert_eq!(1, progress.current_streak());
+        assert_eq!(2, progress.longest_streak());
+
+        let (applications, referrals) = progress.projection().unwrap();
+        assert!((applications - 7.0 / 3.0).abs() < 1e-9);
+        assert_eq!(0.0, referrals);
+
+        let rendered = progress.to_string();
+        assert!(rendered.contains("|2022-W16|0|0|no|2|1|"));
+        assert!(
+            rendered.contains("|Projected for 2022-W18|2.3 applications, 0.0 referrals (behind)|")
+        );
+    }
+
+    #[test]
+    fn test_current_week_extends_streak_once_met() {
+        let goals = GoalsConfig {
+            applications: 1,
+            referrals: 0,
+        };
+        let today = NaiveDate::from_ymd_opt(2022, 5, 4).unwrap();
+        let progress = GoalProgress::new(&input_stats_from_csv(CSV), goals, today);
+
+        // last week and this one, the week of the 18th broke the first streak
+        assert_eq!(2, progress.current_streak());
+        assert_eq!(2, progress.longest_streak());
+    }
+}
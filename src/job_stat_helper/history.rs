@@ -0,0 +1,274 @@
+use std::path::Path;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use super::{InputStat, JobStats};
+
+#[derive(Debug, thiserror::Error)]
+pub enum HistoryError {
+    #[error("failed to read or write the history file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the history file is not valid yaml: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+}
+
+/// Where one application stood when a snapshot was taken
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApplicationState {
+    pub id: String,
+    pub company: String,
+    pub position: String,
+    pub interviewed: bool,
+    pub rejected: bool,
+    pub offered: bool,
+}
+
+impl ApplicationState {
+    fn new(stat: &InputStat) -> Self {
+        Self {
+            id: stat.application_id(),
+            company: stat.company.clone(),
+            position: stat.position.clone(),
+            interviewed: stat.first_interview_date().is_some(),
+            rejected: stat.rejected_dt.is_some(),
+            offered: stat.offer_dt.is_some(),
+        }
+    }
+
+    fn label(&self) -> String {
+        format!("{} - {}", self.company, self.position)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MetricValue {
+    pub name: String,
+    pub value: Option<f64>,
+}
+
+/// The state of the search on the day a report was run
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub taken: NaiveDate,
+    pub applications: Vec<ApplicationState>,
+    pub metrics: Vec<MetricValue>,
+}
+
+impl Snapshot {
+    pub fn new(input_stats: &[InputStat], taken: NaiveDate) -> Self {
+        let job_stats = JobStats::new(input_stats.to_vec());
+
+        Self {
+            taken,
+            applications: input_stats.iter().map(ApplicationState::new).collect(),
+            metrics: job_stats
+                .summary_metrics()
+                .into_iter()
+                .map(|(name, value)| MetricValue {
+                    name: name.to_string(),
+                    value,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Reads every snapshot saved so far, a missing file is an empty history
+pub fn load_history(path: &Path) -> Result<Vec<Snapshot>, HistoryError> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let history = std::fs::read_to_string(path)?;
+    if history.trim().is_empty() {
+        return Ok(vec![]);
+    }
+
+    Ok(serde_yaml::from_str(&history)?)
+}
+
+/// Adds a snapshot to the end of the history file
+pub fn append_snapshot(path: &Path, snapshot: Snapshot) -> Result<(), HistoryError> {
+    let mut history = load_history(path)?;
+    history.push(snapshot);
+    std::fs::write(path, serde_yaml::to_string(&history)?)?;
+
+    Ok(())
+}
+
+/// What changed between two snapshots
+#[derive(Clone, Debug)]
+pub struct SnapshotDiff {
+    pub previous_taken: NaiveDate,
+    pub current_taken: NaiveDate,
+    pub new_applications: Vec<ApplicationState>,
+    pub newly_interviewed: Vec<ApplicationState>,
+    pub newly_rejected: Vec<ApplicationState>,
+    pub new_offers: Vec<ApplicationState>,
+    /// metric name with its previous and current value
+    pub metrics: Vec<(String, Option<f64>, Option<f64>)>,
+}
+
+impl SnapshotDiff {
+    pub fn new(previous: &Snapshot, current: &Snapshot) -> Self {
+        let mut diff = Self {
+            previous_taken: previous.taken,
+            current_taken: current.taken,
+            new_applications: vec![],
+            newly_interviewed: vec![],
+            newly_rejected: vec![],
+            new_offers: vec![],
+            metrics: vec![],
+        };
+
+        for application in &current.applications {
+            let before = previous
+                .applications
+                .iter()
+                .find(|a| a.id == application.id)
+                .cloned();
+
+            let before = match before {
+                Some(b) => b,
+                None => {
+                    diff.new_applications.push(application.clone());
+                    // a new application that already moved along shows up in both lists
+                    ApplicationState {
+                        interviewed: false,
+                        rejected: false,
+                        offered: false,
+                        ..application.clone()
+                    }
+                }
+            };
+
+            if application.interviewed && !before.interviewed {
+                diff.newly_interviewed.push(application.clone());
+            }
+            if application.rejected && !before.rejected {
+                diff.newly_rejected.push(application.clone());
+            }
+            if application.offered && !before.offered {
+                diff.new_offers.push(application.clone());
+            }
+        }
+
+        for metric in &current.metrics {
+            let before = previous
+                .metrics
+                .iter()
+                .find(|m| m.name == metric.name)
+                .and_then(|m| m.value);
+            diff.metrics
+                .push((metric.name.clone(), before, metric.value));
+        }
+
+        diff
+    }
+}
+
+fn labels(applications: &[ApplicationState]) -> String {
+    applications
+        .iter()
+        .map(ApplicationState::label)
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+impl std::fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        writeln!(
+            f,
+            "|Changes from {} to {}|Count|Applications|",
+            self.previous_taken, self.current_taken
+        )?;
+        writeln!(f, "|---|---|---|")?;
+        for (label, applications) in [
+            ("New applications", &self.new_applications),
+            ("Newly interviewed", &self.newly_interviewed),
+            ("Newly rejected", &self.newly_rejected),
+            ("New offers", &self.new_offers),
+        ] {
+            writeln!(
+                f,
+                "|{}|{}|{}|",
+                label,
+                applications.len(),
+                labels(applications)
+            )?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "|Metric|Previous|Current|Change|")?;
+        writeln!(f, "|---|---|---|---|")?;
+        for (name, before, after) in &self.metrics {
+            let change = match (before, after) {
+                (Some(b), Some(a)) => format!("{:+.2}", a - b),
+                _ => String::from("n/a"),
+            };
+            writeln!(
+                f,
+                "|{}|{}|{}|{}|",
+                name,
+                super::display_or_na(*before),
+                super::display_or_na(*after),
+                change
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::input_stats_from_csv;
+
+    const HEADER: &str = "company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes\n";
+
+    #[test]
+    fn test_snapshot_diff() {
+        let before = input_stats_from_csv(&format!(
+            "{HEADER}A,Engineer,20220401,,,,,,,,\nB,Engineer,20220401,,20220405,,,,,,\n"
+        ));
+        let after = input_stats_from_csv(&format!(
+            "{HEADER}A,Engineer,20220401,20220410,,,,,,,\nB,Engineer,20220401,,20220405,20220411,,,,,\nC,Developer,20220409,,,,,,,,\n"
+        ));
+
+        let previous = Snapshot::new(&before, NaiveDate::from_ymd_opt(2022, 4, 8).unwrap());
+        let current = Snapshot::new(&after, NaiveDate::from_ymd_opt(2022, 4, 15).unwrap());
+        let diff = SnapshotDiff::new(&previous, &current);
+
+        assert_eq!(vec!["c-developer-20220409"], ids(&diff.new_applications));
+        assert_eq!(vec!["a-engineer-20220401"], ids(&diff.newly_rejected));
+        assert_eq!(vec!["b-engineer-20220401"], ids(&diff.new_offers));
+        assert!(diff.newly_interviewed.is_empty());
+        assert!(diff
+            .metrics
+            .contains(&(String::from("Jobs applied to"), Some(2.0), Some(3.0))));
+        assert!(diff.to_string().contains("|Jobs applied to|2|3|+1.00|"));
+    }
+
+    #[test]
+    fn test_history_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.yaml");
+        assert!(load_history(&path).unwrap().is_empty());
+
+        let input_stats = input_stats_from_csv(&format!("{HEADER}A,Engineer,20220401,,,,,,,,\n"));
+        let snapshot = Snapshot::new(&input_stats, NaiveDate::from_ymd_opt(2022, 4, 8).unwrap());
+        append_snapshot(&path, snapshot.clone()).unwrap();
+        append_snapshot(&path, snapshot.clone()).unwrap();
+
+        assert_eq!(
+            vec![snapshot.clone(), snapshot],
+            load_history(&path).unwrap()
+        );
+    }
+
+    fn ids(applications: &[ApplicationState]) -> Vec<&str> {
+        applications.iter().map(|a| a.id.as_str()).collect()
+    }
+}
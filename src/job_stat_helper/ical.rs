@@ -0,0 +1,212 @@
+//! Minimal iCalendar (RFC 5545) reading and writing helpers shared by the calendar exports
+use chrono::NaiveDate;
+
+/// the longest a content line may be (in octets) before it has to be folded
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Wraps already rendered components in a VCALENDAR
+pub fn calendar(components: &[String]) -> String {
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, "PRODID:-//rust_examples//job_stats//EN");
+    for component in components {
+        out.push_str(component);
+    }
+    push_line(&mut out, "END:VCALENDAR");
+
+    out
+}
+
+/// Appends a content line folded to 75 octets and terminated with CRLF
+pub fn push_line(out: &mut String, line: &str) {
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            // the leading space of a continuation line counts towards its length
+            octets = 1;
+        }
+        out.push(c);
+        octets += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+/// Escapes a TEXT property value
+pub fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// formats a date as an iCalendar DATE value e.g. `20220408`
+pub fn date_value(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+/// formats a date as a UTC DATE-TIME at midnight, used for DTSTAMP
+pub fn date_time_value(date: NaiveDate) -> String {
+    format!("{}T000000Z", date_value(date))
+}
+
+/// The parts of a VEVENT the job stats care about
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Event {
+    pub summary: String,
+    pub description: Option<String>,
+    pub start: NaiveDate,
+}
+
+/// Reads every VEVENT with a start date out of an iCalendar file
+/// date-times are truncated to their date, events without a DTSTART are skipped
+pub fn parse_events(ics: &str) -> Vec<Event> {
+    let mut events = vec![];
+    let mut in_event = false;
+    let mut summary = String::new();
+    let mut description = None;
+    let mut start = None;
+
+    for line in unfold(ics) {
+        let (name, value) = match split_property(&line) {
+            Some(property) => property,
+            None => continue,
+        };
+
+        match (name.as_str(), value) {
+            ("BEGIN", "VEVENT") => {
+                in_event = true;
+                summary = String::new();
+                description = None;
+                start = None;
+            }
+            ("END", "VEVENT") => {
+                if let Some(start) = start {
+                    events.push(Event {
+                        summary: summary.clone(),
+                        description: description.clone(),
+                        start,
+                    });
+                }
+                in_event = false;
+            }
+            ("SUMMARY", v) if in_event => summary = unescape_text(v),
+            ("DESCRIPTION", v) if in_event => description = Some(unescape_text(v)),
+            ("DTSTART", v) if in_event => {
+                start = v
+                    .get(..8)
+                    .and_then(|d| NaiveDate::parse_from_str(d, "%Y%m%d").ok())
+            }
+            _ => {}
+        }
+    }
+
+    events
+}
+
+/// Joins folded content lines back together
+fn unfold(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for line in ics.lines() {
+        let line = line.trim_end_matches('\r');
+        match (
+            line.strip_prefix(' ').or_else(|| line.strip_prefix('\t')),
+            lines.last_mut(),
+        ) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    lines
+}
+
+/// Splits a content line into its upper cased name (without parameters) and its value
+fn split_property(line: &str) -> Option<(String, &str)> {
+    // parameter values may be quoted and contain colons so skip over quoted sections
+    let mut in_quotes = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ':' if !in_quotes => {
+                let name = line[..i].split(';').next().unwrap_or_default();
+                return Some((name.to_uppercase(), &line[i + 1..]));
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Reverses `escape_text`
+fn unescape_text(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(escaped) => unescaped.push(escaped),
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!("a\\, b\\; c\\\\d\\ne", escape_text("a, b; c\\d\r\ne"));
+    }
+
+    #[test]
+    fn test_push_line_folds_long_lines() {
+        let mut out = String::new();
+        push_line(&mut out, &"x".repeat(100));
+        let lines: Vec<&str> = out.trim_end_matches("\r\n").split("\r\n").collect();
+        assert_eq!(2, lines.len());
+        assert_eq!(75, lines[0].len());
+        assert_eq!(format!(" {}", "x".repeat(25)), lines[1]);
+    }
+
+    #[test]
+    fn test_parse_events() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+                   BEGIN:VEVENT\r\n\
+                   SUMMARY:Interview\\, Acme\r\n\
+                   DTSTART;TZID=\"America/New_York\":20220410T090000\r\n\
+                   DESCRIPTION:a long \r\n  description\r\n\
+                   END:VEVENT\r\n\
+                   BEGIN:VEVENT\r\n\
+                   SUMMARY:no start\r\n\
+                   END:VEVENT\r\n\
+                   END:VCALENDAR\r\n";
+
+        assert_eq!(
+            vec![Event {
+                summary: String::from("Interview, Acme"),
+                description: Some(String::from("a long  description")),
+                start: NaiveDate::from_ymd_opt(2022, 4, 10).unwrap(),
+            }],
+            parse_events(ics)
+        );
+    }
+}
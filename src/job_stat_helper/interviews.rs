@@ -0,0 +1,347 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+
+use super::events::{Event, EventKind};
+use super::{display_or_na, mean, median};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum InterviewType {
+    Phone,
+    Technical,
+    Onsite,
+    Behavioral,
+}
+
+impl std::str::FromStr for InterviewType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "phone" => Ok(InterviewType::Phone),
+            "technical" => Ok(InterviewType::Technical),
+            "onsite" => Ok(InterviewType::Onsite),
+            "behavioral" => Ok(InterviewType::Behavioral),
+            other => Err(format!(
+                "unknown interview type {other}, expected phone, technical, onsite or behavioral"
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for InterviewType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        let name = match self {
+            InterviewType::Phone => "phone",
+            InterviewType::Technical => "technical",
+            InterviewType::Onsite => "onsite",
+            InterviewType::Behavioral => "behavioral",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InterviewRound {
+    pub round: u32,
+    pub date: NaiveDate,
+    pub interview_type: Option<InterviewType>,
+    pub rating: Option<u8>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoopOutcome {
+    Offer,
+    Rejected,
+    /// still waiting, or i declined to continue
+    Open,
+}
+
+/// Every interview round of one application and how the loop ended
+#[derive(Clone, Debug)]
+pub struct InterviewLoop {
+    pub application_id: String,
+    /// ordered by round
+    pub rounds: Vec<InterviewRound>,
+    pub outcome: LoopOutcome,
+}
+
+/// Groups the interview events of the log into one loop per application that was interviewed
+pub fn interview_loops(events: &[Event]) -> Vec<InterviewLoop> {
+    let mut loops: BTreeMap<&str, InterviewLoop> = BTreeMap::new();
+
+    for event in events {
+        let interview_loop = || InterviewLoop {
+            application_id: event.application_id.clone(),
+            rounds: vec![],
+            outcome: LoopOutcome::Open,
+        };
+
+        match &event.kind {
+            EventKind::Interviewed {
+                round,
+                interview_type,
+                rating,
+            } => loops
+                .entry(&event.application_id)
+                .or_insert_with(interview_loop)
+                .rounds
+                .push(InterviewRound {
+                    round: *round,
+                    date: event.date,
+                    interview_type: *interview_type,
+                    rating: *rating,
+                }),
+            EventKind::Offered { .. } => {
+                loops
+                    .entry(&event.application_id)
+                    .or_insert_with(interview_loop)
+                    .outcome = LoopOutcome::Offer
+            }
+            EventKind::Rejected => {
+                loops
+                    .entry(&event.application_id)
+                    .or_insert_with(interview_loop)
+                    .outcome = LoopOutcome::Rejected
+            }
+            _ => {}
+        }
+    }
+
+    loops
+        .into_values()
+        .filter(|l| !l.rounds.is_empty())
+        .map(|mut l| {
+            l.rounds.sort_by_key(|r| (r.round, r.date));
+            l
+        })
+        .collect()
+}
+
+/// How an interview round tends to go
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoundStats {
+    pub round: u32,
+    /// loops that reached this round
+    pub reached: u32,
+    /// moved on to another round or an offer
+    pub passed: u32,
+    /// rejected with this as the last round
+    pub failed: u32,
+    /// days from this round to the next one
+    pub mean_days_to_next: Option<f64>,
+}
+
+/// Metrics over every interview loop in the event log
+#[derive(Clone, Debug)]
+pub struct InterviewStats {
+    pub loops: u32,
+    pub mean_rounds_before_offer: Option<f64>,
+    pub median_rounds_before_offer: Option<f64>,
+    pub mean_rounds_before_rejection: Option<f64>,
+    pub median_rounds_before_rejection: Option<f64>,
+    pub mean_days_between_rounds: Option<f64>,
+    pub median_days_between_rounds: Option<f64>,
+    pub rounds: Vec<RoundStats>,
+    /// mean self rating of rounds that were passed and failed
+    pub mean_rating_passed: Option<f64>,
+    pub mean_rating_failed: Option<f64>,
+}
+
+impl InterviewStats {
+    pub fn new(events: &[Event]) -> Self {
+        let loops = interview_loops(events);
+
+        let mut rounds_before_offer = vec![];
+        let mut rounds_before_rejection = vec![];
+        let mut days_between_rounds = vec![];
+        let mut ratings_passed = vec![];
+        let mut ratings_failed = vec![];
+        let mut rounds: BTreeMap<u32, (RoundStats, Vec<i64>)> = BTreeMap::new();
+
+        for interview_loop in &loops {
+            let count = interview_loop.rounds.len() as i64;
+            match interview_loop.outcome {
+                LoopOutcome::Offer => rounds_before_offer.push(count),
+                LoopOutcome::Rejected => rounds_before_rejection.push(count),
+                LoopOutcome::Open => {}
+            }
+
+            for (i, round) in interview_loop.rounds.iter().enumerate() {
+                let next = interview_loop.rounds.get(i + 1);
+                let (stats, gaps) = rounds.entry(round.round).or_insert_with(|| {
+                    (
+                        RoundStats {
+                            round: round.round,
+                            reached: 0,
+                            passed: 0,
+                            failed: 0,
+                            mean_days_to_next: None,
+                        },
+                        vec![],
+                    )
+                });
+                stats.reached += 1;
+
+                let passed = match (next, interview_loop.outcome) {
+                    (Some(next), _) => {
+                        let gap = (next.date - round.date).num_days();
+                        gaps.push(gap);
+                        days_between_rounds.push(gap);
+                        Some(true)
+                    }
+                    (None, LoopOutcome::Offer) => Some(true),
+                    (None, LoopOutcome::Rejected) => Some(false),
+                    (None, LoopOutcome::Open) => None,
+                };
+
+                match passed {
+                    Some(true) => {
+                        stats.passed += 1;
+                        ratings_passed.extend(round.rating.map(i64::from));
+                    }
+                    Some(false) => {
+                        stats.failed += 1;
+                        ratings_failed.extend(round.rating.map(i64::from));
+                    }
+                    None => {}
+                }
+            }
+        }
+
+        Self {
+            loops: loops.len() as u32,
+            mean_rounds_before_offer: mean(&rounds_before_offer),
+            median_rounds_before_offer: median(&mut rounds_before_offer),
+            mean_rounds_before_rejection: mean(&rounds_before_rejection),
+            median_rounds_before_rejection: median(&mut rounds_before_rejection),
+            mean_days_between_rounds: mean(&days_between_rounds),
+            median_days_between_rounds: median(&mut days_between_rounds),
+            rounds: rounds
+                .into_values()
+                .map(|(mut stats, gaps)| {
+                    stats.mean_days_to_next = mean(&gaps);
+                    stats
+                })
+                .collect(),
+            mean_rating_passed: mean(&ratings_passed),
+            mean_rating_failed: mean(&ratings_failed),
+        }
+    }
+}
+
+impl std::fmt::Display for InterviewStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        writeln!(f, "|Interview loops|Value|")?;
+        writeln!(f, "|---|---|")?;
+        writeln!(f, "|Applications interviewed|{}|", self.loops)?;
+        for (label, value) in [
+            ("Mean rounds before an offer", self.mean_rounds_before_offer),
+            (
+                "Median rounds before an offer",
+                self.median_rounds_before_offer,
+            ),
+            (
+                "Mean rounds before a rejection",
+                self.mean_rounds_before_rejection,
+            ),
+            (
+                "Median rounds before a rejection",
+                self.median_rounds_before_rejection,
+            ),
+            (
+                "Mean time (days) between rounds",
+                self.mean_days_between_rounds,
+            ),
+            (
+                "Median time (days) between rounds",
+                self.median_days_between_rounds,
+            ),
+            ("Mean self rating of passed rounds", self.mean_rating_passed),
+            ("Mean self rating of failed rounds", self.mean_rating_failed),
+        ] {
+            writeln!(f, "|{}|{}|", label, display_or_na(value))?;
+        }
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "|Round|Reached|Passed|Failed|Pass rate|Mean time (days) to next round|"
+        )?;
+        writeln!(f, "|---|---|---|---|---|---|")?;
+        for round in &self.rounds {
+            writeln!(
+                f,
+                "|{}|{}|{}|{}|{}|{}|",
+                round.round,
+                round.reached,
+                round.passed,
+                round.failed,
+                super::significance::format_rate(round.passed, round.passed + round.failed),
+                display_or_na(round.mean_days_to_next)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::events::read_event_log;
+
+    const LOG: &str = "\
+application_id,date,event,round,interview_type,rating,company,position
+a,20220401,applied,,,,Acme,Engineer
+a,20220405,interviewed,1,phone,4,,
+a,20220410,interviewed,2,technical,3,,
+a,20220415,interviewed,3,onsite,5,,
+a,20220420,offered,,,,,
+b,20220401,interviewed,1,phone,2,,
+b,20220409,interviewed,2,technical,2,,
+b,20220412,rejected,,,,,
+c,20220401,interviewed,1,phone,,,
+c,20220403,rejected,,,,,
+d,20220401,interviewed,1,behavioral,,,
+";
+
+    #[test]
+    fn test_interview_stats() {
+        let stats = InterviewStats::new(&read_event_log(LOG.as_bytes()).unwrap());
+
+        assert_eq!(4, stats.loops);
+        assert_eq!(Some(3.0), stats.mean_rounds_before_offer);
+        assert_eq!(Some(1.5), stats.mean_rounds_before_rejection);
+        // 5 and 5 days for a, 8 days for b
+        assert_eq!(Some(6.0), stats.mean_days_between_rounds);
+        assert_eq!(Some(3.5), stats.mean_rating_passed);
+        assert_eq!(Some(2.0), stats.mean_rating_failed);
+
+        assert_eq!(
+            RoundStats {
+                round: 1,
+                reached: 4,
+                passed: 2,
+                failed: 1,
+                mean_days_to_next: Some(6.5),
+            },
+            stats.rounds[0]
+        );
+        assert_eq!(1, stats.rounds[1].failed);
+        assert_eq!(1, stats.rounds[2].passed);
+    }
+
+    #[test]
+    fn test_interview_type_round_trip() {
+        for t in [
+            InterviewType::Phone,
+            InterviewType::Technical,
+            InterviewType::Onsite,
+            InterviewType::Behavioral,
+        ] {
+            assert_eq!(Ok(t), t.to_string().parse());
+        }
+        assert!("Coffee".parse::<InterviewType>().is_err());
+    }
+}
@@ -0,0 +1,481 @@
+use super::business_days::DayCount;
+use super::{display_or_na, mean, median, significance, InputStat};
+
+/// Durations in days between two points of each application that has both
+pub type DaysBetween = fn(&[InputStat], DayCount) -> Vec<i64>;
+
+/// One number computed over the input rows, shown as a row of the report
+pub trait Metric {
+    /// the row label in the report and the name the value is saved under in the history
+    fn name(&self) -> &str;
+
+    /// `None` when there is not enough data to compute it
+    fn compute(&self, input_stats: &[InputStat]) -> Option<f64>;
+
+    /// the value as shown in the report, given the value `compute` returned for the same rows
+    fn format(&self, value: Option<f64>, _input_stats: &[InputStat]) -> String {
+        display_or_na(value)
+    }
+
+    /// rows of the same section are grouped together in the report, with a separator row
+    /// between one section and the next
+    fn section(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// A metric's value for one set of input rows
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetricResult {
+    pub name: String,
+    pub value: Option<f64>,
+    pub formatted: String,
+    pub section: Option<String>,
+}
+
+/// The metrics a report is made of, in the order they are shown
+/// only the rows of the metric table come from here, the referral, channel, offer decision and
+/// business day sections are still fixed parts of `JobStats`
+pub struct MetricRegistry {
+    metrics: Vec<Box<dyn Metric>>,
+}
+
+impl MetricRegistry {
+    /// A registry with no metrics at all
+    pub fn empty() -> Self {
+        Self { metrics: vec![] }
+    }
+
+    /// The metrics `JobStats` has always reported
+    pub fn builtin() -> Self {
+        let mut registry = Self::empty();
+        for metric in builtin_counts() {
+            registry.register(Box::new(metric));
+        }
+        for metric in builtin_durations() {
+            registry.register(Box::new(metric));
+        }
+        registry.register(Box::new(
+            FnMetric::new("Mean offer amount", |input_stats| {
+                mean(&offer_amounts(input_stats))
+            })
+            .in_section(OFFER_AMOUNT_SECTION),
+        ));
+        registry.register(Box::new(
+            FnMetric::new("Median offer amount", |input_stats| {
+                median(&mut offer_amounts(input_stats))
+            })
+            .in_section(OFFER_AMOUNT_SECTION),
+        ));
+
+        registry
+    }
+
+    /// Adds a metric to the end of the report, replacing any metric with the same name in place
+    pub fn register(&mut self, metric: Box<dyn Metric>) -> &mut Self {
+        match self.metrics.iter().position(|m| m.name() == metric.name()) {
+            Some(i) => self.metrics[i] = metric,
+            None => self.metrics.push(metric),
+        }
+        self
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Box<dyn Metric>> {
+        let i = self.metrics.iter().position(|m| m.name() == name)?;
+        Some(self.metrics.remove(i))
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.metrics.iter().map(|m| m.name()).collect()
+    }
+
+    pub fn evaluate(&self, input_stats: &[InputStat]) -> Vec<MetricResult> {
+        self.metrics
+            .iter()
+            .map(|m| {
+                let value = m.compute(input_stats);
+                MetricResult {
+                    name: m.name().to_string(),
+                    value,
+                    formatted: m.format(value, input_stats),
+                    section: m.section().map(String::from),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Default for MetricRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+/// A metric computed by a plain function
+pub struct FnMetric {
+    name: String,
+    compute: fn(&[InputStat]) -> Option<f64>,
+    section: Option<String>,
+}
+
+impl FnMetric {
+    pub fn new(name: &str, compute: fn(&[InputStat]) -> Option<f64>) -> Self {
+        Self {
+            name: name.to_string(),
+            compute,
+            section: None,
+        }
+    }
+
+    pub fn in_section(mut self, section: &str) -> Self {
+        self.section = Some(section.to_string());
+        self
+    }
+}
+
+impl Metric for FnMetric {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn compute(&self, input_stats: &[InputStat]) -> Option<f64> {
+        (self.compute)(input_stats)
+    }
+
+    fn section(&self) -> Option<&str> {
+        self.section.as_deref()
+    }
+}
+
+const COUNT_SECTION: &str = "counts";
+const OFFER_AMOUNT_SECTION: &str = "offer amount";
+
+/// whether a row is counted
+pub type RowFilter = fn(&InputStat) -> bool;
+
+/// How many rows match, optionally shown as a share of the rows matching another filter
+pub struct CountMetric {
+    name: String,
+    counts: RowFilter,
+    /// a label for the whole with its filter
+    share_of: Option<(String, RowFilter)>,
+    section: Option<String>,
+}
+
+impl CountMetric {
+    pub fn new(name: &str, counts: RowFilter) -> Self {
+        Self {
+            name: name.to_string(),
+            counts,
+            share_of: None,
+            section: None,
+        }
+    }
+
+    pub fn in_section(mut self, section: &str) -> Self {
+        self.section = Some(section.to_string());
+        self
+    }
+
+    /// shows the count with its rate and confidence interval out of the rows matching `whole`
+    pub fn share_of(mut self, label: &str, whole: RowFilter) -> Self {
+        self.share_of = Some((label.to_string(), whole));
+        self
+    }
+
+    fn count(input_stats: &[InputStat], filter: RowFilter) -> u32 {
+        input_stats.iter().filter(|s| filter(s)).count() as u32
+    }
+}
+
+impl Metric for CountMetric {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn compute(&self, input_stats: &[InputStat]) -> Option<f64> {
+        Some(Self::count(input_stats, self.counts).into())
+    }
+
+    fn format(&self, value: Option<f64>, input_stats: &[InputStat]) -> String {
+        let count = value.unwrap_or_default() as u32;
+        match &self.share_of {
+            Some((label, whole)) => format!(
+                "{} ({} of {})",
+                count,
+                significance::format_rate(count, Self::count(input_stats, *whole)),
+                label
+            ),
+            None => count.to_string(),
+        }
+    }
+
+    fn section(&self) -> Option<&str> {
+        self.section.as_deref()
+    }
+}
+
+/// How a sample of durations is reduced to one number
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Summary {
+    Mean,
+    Median,
+    Shortest,
+    Longest,
+}
+
+impl Summary {
+    pub const ALL: [Summary; 4] = [
+        Summary::Mean,
+        Summary::Median,
+        Summary::Shortest,
+        Summary::Longest,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Summary::Mean => "Mean",
+            Summary::Median => "Median",
+            Summary::Shortest => "Shortest",
+            Summary::Longest => "Longest",
+        }
+    }
+
+    pub fn apply(&self, durations: &mut [i64]) -> Option<f64> {
+        match self {
+            Summary::Mean => mean(durations),
+            Summary::Median => median(durations),
+            Summary::Shortest => durations.iter().min().map(|d| *d as f64),
+            Summary::Longest => durations.iter().max().map(|d| *d as f64),
+        }
+    }
+}
+
+/// The mean, median, shortest or longest of one kind of duration, in calendar days
+/// durations between the same two points share a section
+pub struct DurationMetric {
+    name: String,
+    between: String,
+    days_between: DaysBetween,
+    summary: Summary,
+}
+
+impl DurationMetric {
+    /// named e.g. `Mean time (days) between application and rejection` for `between` of
+    /// `application and rejection`
+    pub fn new(between: &str, days_between: DaysBetween, summary: Summary) -> Self {
+        Self {
+            name: format!("{} time (days) between {}", summary.label(), between),
+            between: between.to_string(),
+            days_between,
+            summary,
+        }
+    }
+}
+
+impl Metric for DurationMetric {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn compute(&self, input_stats: &[InputStat]) -> Option<f64> {
+        self.summary
+            .apply(&mut (self.days_between)(input_stats, DayCount::Calendar))
+    }
+
+    fn section(&self) -> Option<&str> {
+        Some(&self.between)
+    }
+}
+
+/// The kinds of duration reported, with what they are between
+pub(super) fn builtin_days_between() -> [(&'static str, DaysBetween); 4] {
+    [
+        (
+            "application date and first interview",
+            super::days_between_application_and_first_interview,
+        ),
+        (
+            "application and rejection",
+            super::days_between_application_and_rejection,
+        ),
+        (
+            "first interview and rejection",
+            super::days_between_first_interview_and_rejection,
+        ),
+        (
+            "first interview and offer",
+            super::days_between_first_interview_and_offer,
+        ),
+    ]
+}
+
+fn builtin_durations() -> Vec<DurationMetric> {
+    builtin_days_between()
+        .into_iter()
+        .flat_map(|(between, days_between)| {
+            Summary::ALL
+                .into_iter()
+                .map(move |summary| DurationMetric::new(between, days_between, summary))
+        })
+        .collect()
+}
+
+fn builtin_counts() -> Vec<CountMetric> {
+    let all = |_: &InputStat| true;
+    let rejected = |s: &InputStat| s.rejected_dt.is_some();
+
+    let counts = vec![
+        CountMetric::new("Jobs applied to", all),
+        CountMetric::new("Count of referrals", |s| s.is_referral())
+            .share_of("all jobs applied to", all),
+        CountMetric::new("First interviews completed", |s| {
+            s.first_interview.is_some()
+        })
+        .share_of("jobs applied to", all),
+        CountMetric::new("First interviews I declined", |s| {
+            s.declined_first_interview()
+        })
+        .share_of("all jobs applied to", all),
+        CountMetric::new("Offers", |s| s.offer_dt.is_some()).share_of("all jobs applied to", all),
+        CountMetric::new("Rejections (all types)", rejected).share_of("all jobs applied to", all),
+        CountMetric::new("Rejections without first interview", |s| {
+            s.rejected_dt.is_some() && s.first_interview.is_none()
+        })
+        .share_of("rejections (all types)", rejected),
+        CountMetric::new("Rejection after first interview", |s| {
+            s.first_interview.is_some() && s.rejected_dt.is_some()
+        })
+        .share_of("rejections (all types)", rejected),
+        CountMetric::new("No Response following application", |s| {
+            s.rejected_dt.is_none() && s.first_interview.is_none()
+        })
+        .share_of("all jobs applied to", all),
+    ];
+
+    counts
+        .into_iter()
+        .map(|count| count.in_section(COUNT_SECTION))
+        .collect()
+}
+
+/// offer amounts rounded to whole units, only meaningful once they share a currency
+fn offer_amounts(input_stats: &[InputStat]) -> Vec<i64> {
+    input_stats
+        .iter()
+        .filter_map(|stat| stat.offer_amt)
+        .map(|amount| amount.round() as i64)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::{input_stats_from_csv, JobStats};
+
+    const CSV: &str = "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes
+A,Engineer,20220401,,20220405,20220420,100000,,yes,,
+B,Engineer,20220401,20220410,20220404,,,,,,
+C,Engineer,20220401,20220405,,,,,,,
+D,Engineer,20220401,,-1,,,,,,
+";
+
+    /// a metric another crate could write with the public accessors
+    struct RemoteShare;
+
+    impl Metric for RemoteShare {
+        fn name(&self) -> &str {
+            "Remote positions"
+        }
+
+        fn compute(&self, input_stats: &[InputStat]) -> Option<f64> {
+            Some(
+                input_stats
+                    .iter()
+                    .filter(|s| s.position().contains("Remote"))
+                    .count() as f64,
+            )
+        }
+    }
+
+    #[test]
+    fn test_builtin_metrics() {
+        let results = MetricRegistry::builtin().evaluate(&input_stats_from_csv(CSV));
+        let find = |name: &str| results.iter().find(|r| r.name == name).unwrap();
+
+        assert_eq!(27, results.len());
+        assert_eq!(Some(4.0), find("Jobs applied to").value);
+        assert_eq!(
+            "1 (1/4 = 25.00% [4.56%, 69.94%]* of all jobs applied to)",
+            find("Offers").formatted
+        );
+        assert_eq!(Some(1.0), find("Rejections without first interview").value);
+        // only applications that got an offer count here
+        assert_eq!(
+            Some(4.0),
+            find("Mean time (days) between application date and first interview").value
+        );
+        assert_eq!(
+            Some(6.0),
+            find("Longest time (days) between first interview and rejection").value
+        );
+        assert_eq!("100000", find("Mean offer amount").formatted);
+        assert_eq!(
+            "15",
+            find("Median time (days) between first interview and offer").formatted
+        );
+
+        // sections are kept apart by separator rows
+        let rendered = JobStats::new(input_stats_from_csv(CSV)).to_string();
+        assert!(rendered.contains(
+            "of all jobs applied to)|\n|----|---|\n|Mean time (days) between application date"
+        ));
+        assert!(rendered.contains(
+            "|Longest time (days) between application date and first interview|4|\n|---|---|\n"
+        ));
+        assert!(rendered.contains("|---|---|\n|Mean offer amount|100000|"));
+    }
+
+    #[test]
+    fn test_no_response_share_of_all_applications() {
+        // more applications went unanswered than were rejected
+        let results = MetricRegistry::builtin().evaluate(&input_stats_from_csv(
+            "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes
+A,Engineer,20220401,20220405,,,,,,,
+B,Engineer,20220401,,,,,,,,
+C,Engineer,20220401,,,,,,,,
+D,Engineer,20220401,,,,,,,,
+",
+        ));
+        let find = |name: &str| results.iter().find(|r| r.name == name).unwrap();
+
+        assert_eq!(
+            "3 (3/4 = 75.00% [30.06%, 95.44%]* of all jobs applied to)",
+            find("No Response following application").formatted
+        );
+    }
+
+    #[test]
+    fn test_register_custom_metric() {
+        let mut registry = MetricRegistry::builtin();
+        registry.register(Box::new(RemoteShare));
+        registry.register(Box::new(CountMetric::new("Offers", |s| {
+            s.offer_date().is_some()
+        })));
+        assert!(registry.remove("Median offer amount").is_some());
+
+        let names = registry.names();
+        assert_eq!(Some(&"Remote positions"), names.last());
+        // replaced where it was rather than moved to the end
+        assert_eq!(4, names.iter().position(|n| *n == "Offers").unwrap());
+
+        let job_stats = JobStats::with_registry(input_stats_from_csv(CSV), &registry);
+        assert_eq!(Some(0.0), job_stats.value("Remote positions"));
+        assert_eq!(None, job_stats.value("Median offer amount"));
+        let rendered = job_stats.to_string();
+        assert!(rendered.contains("|Remote positions|0|"));
+        assert!(rendered.contains("|Offers|1|"));
+    }
+}
@@ -0,0 +1,271 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::InputStat;
+
+/// How many years of compensation an offer is compared over
+pub const COMPARISON_YEARS: usize = 4;
+
+#[derive(Debug, thiserror::Error)]
+pub enum OfferDetailsError {
+    #[error("failed to read the offer details file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the offer details file is not valid yaml: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("the equity vesting schedule of {0} adds up to {1}% rather than 100%")]
+    Vesting(String, f64),
+}
+
+/// A stock grant and the percent of it that vests in each year after joining
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EquityGrant {
+    /// value of the whole grant at the time of the offer
+    pub total: f64,
+    #[serde(default = "even_vesting")]
+    pub vesting: Vec<f64>,
+}
+
+fn even_vesting() -> Vec<f64> {
+    vec![25.0; COMPARISON_YEARS]
+}
+
+/// The full compensation of an offer, linked to the application by its id
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OfferDetails {
+    pub application_id: String,
+    pub base: f64,
+    /// yearly target bonus
+    #[serde(default)]
+    pub bonus: f64,
+    /// paid once in the first year
+    #[serde(default)]
+    pub sign_on: f64,
+    pub equity: Option<EquityGrant>,
+    /// the currency code every amount is in, taken to be the reporting currency when missing
+    #[serde(default)]
+    pub currency: Option<String>,
+}
+
+impl OfferDetails {
+    /// Total compensation in each of the first `COMPARISON_YEARS` years
+    pub fn year_totals(&self) -> [f64; COMPARISON_YEARS] {
+        let mut totals = [self.base + self.bonus; COMPARISON_YEARS];
+        totals[0] += self.sign_on;
+
+        if let Some(equity) = &self.equity {
+            for (total, percent) in totals.iter_mut().zip(&equity.vesting) {
+                *total += equity.total * percent / 100.0;
+            }
+        }
+
+        totals
+    }
+
+    /// The mean yearly value over the comparison years, sign on and equity included
+    pub fn annualized(&self) -> f64 {
+        self.year_totals().iter().sum::<f64>() / COMPARISON_YEARS as f64
+    }
+
+    /// Every amount multiplied by `rate`, e.g. to convert them to another currency
+    pub fn scaled(&self, rate: f64) -> Self {
+        Self {
+            base: self.base * rate,
+            bonus: self.bonus * rate,
+            sign_on: self.sign_on * rate,
+            equity: self.equity.as_ref().map(|e| EquityGrant {
+                total: e.total * rate,
+                vesting: e.vesting.clone(),
+            }),
+            ..self.clone()
+        }
+    }
+}
+
+/// Reads the offer details kept next to the input csv
+pub fn load_offer_details(path: &Path) -> Result<Vec<OfferDetails>, OfferDetailsError> {
+    let details: Vec<OfferDetails> = serde_yaml::from_str(&std::fs::read_to_string(path)?)?;
+
+    for offer in &details {
+        if let Some(equity) = &offer.equity {
+            let vested: f64 = equity.vesting.iter().sum();
+            if (vested - 100.0).abs() > 0.01 {
+                return Err(OfferDetailsError::Vesting(
+                    offer.application_id.clone(),
+                    vested,
+                ));
+            }
+        }
+    }
+
+    Ok(details)
+}
+
+/// Offers side by side, highest annualized value first
+#[derive(Clone, Debug)]
+pub struct OfferComparison {
+    /// the application label with its details
+    pub offers: Vec<(String, OfferDetails)>,
+    /// ids in the details file that match no application
+    pub unmatched: Vec<String>,
+}
+
+impl OfferComparison {
+    pub fn new(input_stats: &[InputStat], details: &[OfferDetails]) -> Self {
+        let mut comparison = Self {
+            offers: vec![],
+            unmatched: vec![],
+        };
+
+        for offer in details {
+            match input_stats
+                .iter()
+                .find(|s| s.application_id() == offer.application_id)
+            {
+                Some(stat) => comparison.offers.push((
+                    format!("{} - {}", stat.company, stat.position),
+                    offer.clone(),
+                )),
+                None => comparison.unmatched.push(offer.application_id.clone()),
+            }
+        }
+
+        comparison
+            .offers
+            .sort_by(|(_, a), (_, b)| b.annualized().total_cmp(&a.annualized()));
+
+        comparison
+    }
+
+    /// one row of the comparison with a value for every offer
+    fn write_row(
+        &self,
+        f: &mut std::fmt::Formatter,
+        label: &str,
+        value: impl Fn(&OfferDetails) -> f64,
+    ) -> std::fmt::Result {
+        write!(f, "|{}|", label)?;
+        for (_, offer) in &self.offers {
+            write!(f, "{:.0}|", value(offer))?;
+        }
+        writeln!(f)
+    }
+}
+
+impl std::fmt::Display for OfferComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "|Offer comparison|")?;
+        for (label, _) in &self.offers {
+            write!(f, "{}|", label)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "|---|{}", "---|".repeat(self.offers.len()))?;
+
+        self.write_row(f, "Base", |o| o.base)?;
+        self.write_row(f, "Bonus", |o| o.bonus)?;
+        self.write_row(f, "Sign on", |o| o.sign_on)?;
+        self.write_row(f, "Equity grant", |o| {
+            o.equity.as_ref().map_or(0.0, |e| e.total)
+        })?;
+        for year in 0..COMPARISON_YEARS {
+            self.write_row(f, &format!("Year {} total", year + 1), |o| {
+                o.year_totals()[year]
+            })?;
+        }
+        self.write_row(f, "Annualized", OfferDetails::annualized)?;
+
+        if !self.unmatched.is_empty() {
+            writeln!(f)?;
+            writeln!(
+                f,
+                "Offer details with no matching application: {}",
+                self.unmatched.join(", ")
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::input_stats_from_csv;
+
+    const DETAILS: &str = "
+- application_id: acme-engineer-20220401
+  base: 100000
+  bonus: 10000
+  sign_on: 20000
+  equity:
+    total: 80000
+    vesting: [10, 20, 30, 40]
+- application_id: globex-engineer-20220402
+  base: 120000
+- application_id: initech-engineer-20220403
+  base: 90000
+";
+
+    #[test]
+    fn test_year_totals() {
+        let details: Vec<OfferDetails> = serde_yaml::from_str(DETAILS).unwrap();
+
+        assert_eq!(
+            [138000.0, 126000.0, 134000.0, 142000.0],
+            details[0].year_totals()
+        );
+        assert_eq!(135000.0, details[0].annualized());
+        assert_eq!(120000.0, details[1].annualized());
+    }
+
+    #[test]
+    fn test_offer_comparison() {
+        let input_stats = input_stats_from_csv(
+            "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes
+Acme,Engineer,20220401,,20220405,20220420,100000,,,,
+Globex,Engineer,20220402,,20220405,20220420,120000,,,,
+",
+        );
+        let details: Vec<OfferDetails> = serde_yaml::from_str(DETAILS).unwrap();
+        let comparison = OfferComparison::new(&input_stats, &details);
+
+        assert_eq!("Acme - Engineer", comparison.offers[0].0);
+        assert_eq!(vec!["initech-engineer-20220403"], comparison.unmatched);
+
+        let rendered = comparison.to_string();
+        assert!(rendered.contains("|Offer comparison|Acme - Engineer|Globex - Engineer|"));
+        assert!(rendered.contains("|Year 1 total|138000|120000|"));
+        assert!(rendered.contains("|Annualized|135000|120000|"));
+    }
+
+    #[test]
+    fn test_vesting_must_add_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("offers.yaml");
+        std::fs::write(
+            &path,
+            "- application_id: a\n  base: 1\n  equity:\n    total: 10\n    vesting: [25, 25]\n",
+        )
+        .unwrap();
+
+        assert!(matches!(
+            load_offer_details(&path),
+            Err(OfferDetailsError::Vesting(_, v)) if v == 50.0
+        ));
+
+        std::fs::write(
+            &path,
+            "- application_id: a\n  base: 1\n  equity:\n    total: 10\n",
+        )
+        .unwrap();
+        assert_eq!(
+            even_vesting(),
+            load_offer_details(&path).unwrap()[0]
+                .equity
+                .as_ref()
+                .unwrap()
+                .vesting
+        );
+    }
+}
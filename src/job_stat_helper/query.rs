@@ -0,0 +1,586 @@
+use chrono::NaiveDate;
+
+use super::InputStat;
+
+/// What went wrong parsing a query and where, `column` counts characters from 1
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("{message} at column {column}")]
+pub struct QueryError {
+    pub column: usize,
+    pub message: String,
+}
+
+impl QueryError {
+    fn new(column: usize, message: impl Into<String>) -> Self {
+        Self {
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// The query with a `^` under the column the error is at
+    pub fn pointer(&self, query: &str) -> String {
+        format!("{}\n{}^", query, " ".repeat(self.column.saturating_sub(1)))
+    }
+}
+
+/// What a field's values are compared as
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldKind {
+    Text,
+    Date,
+    Number,
+}
+
+/// A column of `InputStat` a query can test
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    Company,
+    Position,
+    Applied,
+    Rejected,
+    /// also `interview`, present for an interview i declined but with no date to compare
+    FirstInterview,
+    Offer,
+    OfferAmt,
+    Currency,
+    Accepted,
+    Decision,
+    /// present for applications through the referral channel
+    Referral,
+    Url,
+    Notes,
+    Owner,
+    ResumeVersion,
+    Channel,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Self> {
+        let field = match name {
+            "company" => Field::Company,
+            "position" => Field::Position,
+            "applied" => Field::Applied,
+            "rejected" => Field::Rejected,
+            "first_interview" | "interview" => Field::FirstInterview,
+            "offer" => Field::Offer,
+            "offer_amt" => Field::OfferAmt,
+            "currency" => Field::Currency,
+            "accepted" => Field::Accepted,
+            "decision" => Field::Decision,
+            "referral" => Field::Referral,
+            "url" => Field::Url,
+            "notes" => Field::Notes,
+            "owner" => Field::Owner,
+            "resume_version" => Field::ResumeVersion,
+            "channel" => Field::Channel,
+            _ => return None,
+        };
+        Some(field)
+    }
+
+    pub fn kind(&self) -> FieldKind {
+        match self {
+            Field::Applied
+            | Field::Rejected
+            | Field::FirstInterview
+            | Field::Offer
+            | Field::Decision => FieldKind::Date,
+            Field::OfferAmt => FieldKind::Number,
+            _ => FieldKind::Text,
+        }
+    }
+
+    fn text(&self, stat: &InputStat) -> Option<String> {
+        match self {
+            Field::Company => Some(stat.company.clone()),
+            Field::Position => Some(stat.position.clone()),
+            Field::Currency => stat.currency.clone(),
+            Field::Accepted => stat.accepted.clone(),
+            Field::Referral => stat.referral.clone(),
+            Field::Url => stat.url.clone(),
+            Field::Notes => stat.notes.clone(),
+            Field::Owner => stat.owner.clone(),
+            Field::ResumeVersion => stat.resume_version.clone(),
+            Field::Channel => stat.channel().and_then(Result::ok).map(|c| c.to_string()),
+            _ => None,
+        }
+    }
+
+    fn date(&self, stat: &InputStat) -> Option<NaiveDate> {
+        match self {
+            Field::Applied => Some(stat.applied_date()),
+            Field::Rejected => stat.rejected_date(),
+            Field::FirstInterview => stat.first_interview_date(),
+            Field::Offer => stat.offer_date(),
+            Field::Decision => stat.decision_date(),
+            _ => None,
+        }
+    }
+
+    fn is_present(&self, stat: &InputStat) -> bool {
+        match self {
+            Field::FirstInterview => stat.first_interview.is_some(),
+            Field::Referral => stat.is_referral(),
+            Field::OfferAmt => stat.offer_amt.is_some(),
+            field if field.kind() == FieldKind::Date => field.date(stat).is_some(),
+            field => field.text(stat).map_or(false, |t| !t.trim().is_empty()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    /// text contains, ignoring case
+    Contains,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl CompareOp {
+    fn symbol(&self) -> &'static str {
+        match self {
+            CompareOp::Eq => "=",
+            CompareOp::Ne => "!=",
+            CompareOp::Contains => "~",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+        }
+    }
+
+    fn holds(&self, ordering: std::cmp::Ordering) -> bool {
+        use std::cmp::Ordering::*;
+        match self {
+            CompareOp::Eq => ordering == Equal,
+            CompareOp::Ne => ordering != Equal,
+            CompareOp::Lt => ordering == Less,
+            CompareOp::Le => ordering != Greater,
+            CompareOp::Gt => ordering == Greater,
+            CompareOp::Ge => ordering != Less,
+            CompareOp::Contains => false,
+        }
+    }
+}
+
+/// A value to compare a field with, of the field's kind
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Text(String),
+    Date(NaiveDate),
+    Number(f64),
+}
+
+/// A parsed query
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    /// a field on its own, whether the application has a value for it
+    Present(Field),
+    /// a comparison with a missing value is false
+    Compare(Field, CompareOp, Value),
+    /// `#remote`, whether the notes have the hashtag
+    Hashtag(String),
+}
+
+impl Expr {
+    pub fn matches(&self, stat: &InputStat) -> bool {
+        match self {
+            Expr::And(a, b) => a.matches(stat) && b.matches(stat),
+            Expr::Or(a, b) => a.matches(stat) || b.matches(stat),
+            Expr::Not(e) => !e.matches(stat),
+            Expr::Present(field) => field.is_present(stat),
+            Expr::Hashtag(hashtag) => stat.tags().hashtags.contains(hashtag),
+            Expr::Compare(field, op, value) => match value {
+                Value::Text(wanted) => field.text(stat).map_or(false, |text| {
+                    let (text, wanted) = (text.to_lowercase(), wanted.to_lowercase());
+                    match op {
+                        CompareOp::Contains => text.contains(&wanted),
+                        op => op.holds(text.trim().cmp(wanted.trim())),
+                    }
+                }),
+                Value::Date(wanted) => field
+                    .date(stat)
+                    .map_or(false, |date| op.holds(date.cmp(wanted))),
+                Value::Number(wanted) => stat
+                    .offer_amt
+                    .and_then(|amount| amount.partial_cmp(wanted))
+                    .map_or(false, |ordering| op.holds(ordering)),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    /// a quoted string, kept apart from words so `"and"` is not a keyword
+    Quoted(String),
+    Hashtag(String),
+    Op(CompareOp),
+    LParen,
+    RParen,
+    End,
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Word(w) => format!("{:?}", w),
+        Token::Quoted(s) => format!("the string {:?}", s),
+        Token::Hashtag(h) => format!("#{}", h),
+        Token::Op(op) => format!("{:?}", op.symbol()),
+        Token::LParen => String::from("\"(\""),
+        Token::RParen => String::from("\")\""),
+        Token::End => String::from("the end of the query"),
+    }
+}
+
+/// the tokens of the query with the column each starts at
+fn tokenize(query: &str) -> Result<Vec<(Token, usize)>, QueryError> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        let column = i + 1;
+        let c = chars[i];
+        let token = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => {
+                i += 1;
+                Token::LParen
+            }
+            ')' => {
+                i += 1;
+                Token::RParen
+            }
+            '"' => {
+                let mut text = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(QueryError::new(column, "unterminated string")),
+                        Some('"') => break,
+                        Some('\\') if chars.get(i + 1).is_some() => {
+                            text.push(chars[i + 1]);
+                            i += 2;
+                        }
+                        Some(c) => {
+                            text.push(*c);
+                            i += 1;
+                        }
+                    }
+                }
+                i += 1;
+                Token::Quoted(text)
+            }
+            '=' | '~' => {
+                i += 1;
+                Token::Op(if c == '=' {
+                    CompareOp::Eq
+                } else {
+                    CompareOp::Contains
+                })
+            }
+            '!' | '<' | '>' => {
+                let with_eq = chars.get(i + 1) == Some(&'=');
+                i += if with_eq { 2 } else { 1 };
+                Token::Op(match (c, with_eq) {
+                    ('!', true) => CompareOp::Ne,
+                    ('<', false) => CompareOp::Lt,
+                    ('<', true) => CompareOp::Le,
+                    ('>', false) => CompareOp::Gt,
+                    ('>', true) => CompareOp::Ge,
+                    _ => return Err(QueryError::new(column, "expected != after !")),
+                })
+            }
+            _ => {
+                let start = i;
+                while i < chars.len() && is_word_char(chars[i]) {
+                    i += 1;
+                }
+                if i == start {
+                    return Err(QueryError::new(
+                        column,
+                        format!("unexpected character {:?}", c),
+                    ));
+                }
+                let word: String = chars[start..i].iter().collect();
+                match word.strip_prefix('#') {
+                    Some("") => return Err(QueryError::new(column, "expected a tag after #")),
+                    Some(hashtag) => Token::Hashtag(hashtag.to_lowercase()),
+                    None => Token::Word(word),
+                }
+            }
+        };
+        tokens.push((token, column));
+    }
+    tokens.push((Token::End, chars.len() + 1));
+
+    Ok(tokens)
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '#')
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &(Token, usize) {
+        &self.tokens[self.position]
+    }
+
+    fn next(&mut self) -> (Token, usize) {
+        let token = self.tokens[self.position].clone();
+        if token.0 != Token::End {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.peek().0, Token::Word(w) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    fn or(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.and()?;
+        while self.at_keyword("or") {
+            self.next();
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.not()?;
+        while self.at_keyword("and") {
+            self.next();
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, QueryError> {
+        if self.at_keyword("not") {
+            self.next();
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr, QueryError> {
+        match self.next() {
+            (Token::LParen, column) => {
+                let expr = self.or()?;
+                match self.next() {
+                    (Token::RParen, _) => Ok(expr),
+                    (token, at) => Err(QueryError::new(
+                        at,
+                        format!(
+                            "expected \")\" to close the \"(\" at column {}, found {}",
+                            column,
+                            describe(&token)
+                        ),
+                    )),
+                }
+            }
+            (Token::Hashtag(hashtag), _) => Ok(Expr::Hashtag(hashtag)),
+            (Token::Word(name), column) => {
+                let field = Field::from_name(&name.to_lowercase())
+                    .ok_or_else(|| QueryError::new(column, format!("unknown field {:?}", name)))?;
+                match self.peek().0 {
+                    Token::Op(CompareOp::Contains) if field.kind() != FieldKind::Text => {
+                        Err(QueryError::new(
+                            self.peek().1,
+                            format!("~ only works on text and {} is not text", name),
+                        ))
+                    }
+                    Token::Op(op) => {
+                        self.next();
+                        self.comparison(field, &name, op)
+                    }
+                    _ => Ok(Expr::Present(field)),
+                }
+            }
+            (token, column) => Err(QueryError::new(
+                column,
+                format!(
+                    "expected a field, #tag or \"(\", found {}",
+                    describe(&token)
+                ),
+            )),
+        }
+    }
+
+    fn comparison(&mut self, field: Field, name: &str, op: CompareOp) -> Result<Expr, QueryError> {
+        let (token, column) = self.next();
+        let text = match token {
+            Token::Word(text) | Token::Quoted(text) => text,
+            token => {
+                return Err(QueryError::new(
+                    column,
+                    format!(
+                        "expected a value after {}, found {}",
+                        op.symbol(),
+                        describe(&token)
+                    ),
+                ))
+            }
+        };
+
+        let value = match field.kind() {
+            FieldKind::Text => Value::Text(text),
+            FieldKind::Date => NaiveDate::parse_from_str(&text, "%Y-%m-%d")
+                .or_else(|_| NaiveDate::parse_from_str(&text, "%Y%m%d"))
+                .map(Value::Date)
+                .map_err(|_| {
+                    QueryError::new(
+                        column,
+                        format!("{} is a date, expected YYYY-MM-DD not {:?}", name, text),
+                    )
+                })?,
+            FieldKind::Number => text.parse().map(Value::Number).map_err(|_| {
+                QueryError::new(
+                    column,
+                    format!("{} is a number, expected one not {:?}", name, text),
+                )
+            })?,
+        };
+
+        Ok(Expr::Compare(field, op, value))
+    }
+}
+
+/// Parses a query such as
+/// `company ~ "acme" and applied >= 2022-03-01 and (offer or interview) and not referral`
+/// `and` binds tighter than `or`, text comparisons ignore case and dates are YYYY-MM-DD
+pub fn parse_query(query: &str) -> Result<Expr, QueryError> {
+    let mut parser = Parser {
+        tokens: tokenize(query)?,
+        position: 0,
+    };
+    let expr = parser.or()?;
+
+    match parser.next() {
+        (Token::End, _) => Ok(expr),
+        (token, column) => Err(QueryError::new(
+            column,
+            format!(
+                "expected and, or or the end of the query, found {}",
+                describe(&token)
+            ),
+        )),
+    }
+}
+
+/// The applications the query matches
+pub fn select(input_stats: &[InputStat], expr: &Expr) -> Vec<InputStat> {
+    input_stats
+        .iter()
+        .filter(|stat| expr.matches(stat))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::input_stats_from_csv;
+
+    const CSV: &str = "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes
+Acme,Engineer,20220305,,20220310,,,,,,#remote
+ACME Inc.,Engineer,20220306,,,20220320,120000,,Jane Doe,,
+Acme,Engineer,20220201,,20220210,,,,,,
+Globex,Engineer,20220305,,-1,,,,,,
+";
+
+    fn companies(query: &str) -> Vec<String> {
+        select(&input_stats_from_csv(CSV), &parse_query(query).unwrap())
+            .iter()
+            .map(|s| s.company.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let date = NaiveDate::from_ymd_opt(2022, 3, 1).unwrap();
+        assert_eq!(
+            Expr::Or(
+                Box::new(Expr::And(
+                    Box::new(Expr::Compare(
+                        Field::Company,
+                        CompareOp::Contains,
+                        Value::Text(String::from("acme"))
+                    )),
+                    Box::new(Expr::Compare(
+                        Field::Applied,
+                        CompareOp::Ge,
+                        Value::Date(date)
+                    )),
+                )),
+                Box::new(Expr::Not(Box::new(Expr::Present(Field::Referral)))),
+            ),
+            parse_query("company ~ \"acme\" and applied >= 2022-03-01 or not referral").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_select() {
+        assert_eq!(
+            vec!["Acme"],
+            companies(
+                "company ~ \"acme\" and applied >= 2022-03-01 and (offer or interview) and not referral"
+            )
+        );
+        assert_eq!(
+            vec!["ACME Inc."],
+            companies("offer_amt > 100000 and referral")
+        );
+        assert_eq!(
+            vec!["Globex"],
+            companies("interview and not first_interview > 20220101")
+        );
+        assert_eq!(vec!["Acme"], companies("#remote"));
+        assert_eq!(vec!["Acme", "Acme"], companies("company = ACME"));
+    }
+
+    #[test]
+    fn test_query_errors() {
+        let error = |query: &str| parse_query(query).unwrap_err();
+
+        assert_eq!(
+            QueryError::new(1, "unknown field \"compny\""),
+            error("compny ~ \"acme\"")
+        );
+        assert_eq!(
+            "expected \")\" to close the \"(\" at column 11, found the end of the query at column 30",
+            error("offer and (interview or offer").to_string()
+        );
+        assert_eq!(
+            "applied is a date, expected YYYY-MM-DD not \"March\" at column 12",
+            error("applied >= March").to_string()
+        );
+        assert_eq!(11, error("offer_amt ~ 100").column);
+        assert_eq!(9, error("company \"acme\"").column);
+        assert_eq!(11, error("company = \"acme").column);
+        assert_eq!(
+            "offer and (\n           ^",
+            error("offer and (").pointer("offer and (")
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use super::significance;
+use super::InputStat;
+
+/// How far one group of applications got
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GroupOutcomes {
+    pub applied: u32,
+    pub interviews: u32,
+    pub offers: u32,
+}
+
+impl GroupOutcomes {
+    fn from_input_stats<'a>(input_stats: impl Iterator<Item = &'a InputStat>) -> Self {
+        input_stats.fold(Self::default(), |mut outcomes, stat| {
+            outcomes.applied += 1;
+            // an interview i declined still counts as the company responding with one
+            if stat.first_interview.is_some() {
+                outcomes.interviews += 1;
+            }
+            if stat.offer_dt.is_some() {
+                outcomes.offers += 1;
+            }
+            outcomes
+        })
+    }
+}
+
+/// p-values for whether a rate differs between two groups of applications, e.g. referral and
+/// cold ones
+#[derive(Clone, Copy, Debug)]
+pub struct RateComparison {
+    pub fisher_p: f64,
+    /// the chi-square statistic and its p-value, `None` if a group or outcome is empty
+    pub chi_square: Option<(f64, f64)>,
+    /// chi-square is only trustworthy when every expected cell is at least 5
+    pub chi_square_unreliable: bool,
+}
+
+impl RateComparison {
+    pub(super) fn new(successes_a: u32, trials_a: u32, successes_b: u32, trials_b: u32) -> Self {
+        let (a, b) = (successes_a, trials_a - successes_a);
+        let (c, d) = (successes_b, trials_b - successes_b);
+
+        Self {
+            fisher_p: significance::fisher_exact(a, b, c, d),
+            chi_square: significance::chi_square_2x2(a, b, c, d),
+            chi_square_unreliable: significance::chi_square_unreliable(a, b, c, d),
+        }
+    }
+}
+
+/// Compares interview and offer rates of referral against cold (non referral) applications
+#[derive(Clone, Copy, Debug)]
+pub struct ReferralEffectiveness {
+    pub referral: GroupOutcomes,
+    pub cold: GroupOutcomes,
+    pub interview_rate: RateComparison,
+    pub offer_rate: RateComparison,
+}
+
+impl ReferralEffectiveness {
+    pub fn new(input_stats: &[InputStat]) -> Self {
+        let referral =
+            GroupOutcomes::from_input_stats(input_stats.iter().filter(|s| s.is_referral()));
+        let cold = GroupOutcomes::from_input_stats(input_stats.iter().filter(|s| !s.is_referral()));
+
+        Self {
+            referral,
+            cold,
+            interview_rate: RateComparison::new(
+                referral.interviews,
+                referral.applied,
+                cold.interviews,
+                cold.applied,
+            ),
+            offer_rate: RateComparison::new(
+                referral.offers,
+                referral.applied,
+                cold.offers,
+                cold.applied,
+            ),
+        }
+    }
+
+    /// whether either group has too few applications for its rates to mean much
+    pub fn has_small_sample(&self) -> bool {
+        self.referral.applied < significance::SMALL_SAMPLE_SIZE
+            || self.cold.applied < significance::SMALL_SAMPLE_SIZE
+    }
+}
+
+pub(super) fn p_values(comparison: &RateComparison) -> String {
+    let chi_square = match comparison.chi_square {
+        Some((statistic, p)) if comparison.chi_square_unreliable => {
+            format!("χ²={:.2} p={:.4} (expected counts under 5)", statistic, p)
+        }
+        Some((statistic, p)) => format!("χ²={:.2} p={:.4}", statistic, p),
+        None => String::from("χ² n/a"),
+    };
+
+    format!("Fisher p={:.4}, {}", comparison.fisher_p, chi_square)
+}
+
+impl std::fmt::Display for ReferralEffectiveness {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        writeln!(
+            f,
+            "|Referral effectiveness|Applied|Interview rate (95% CI)|Offer rate (95% CI)|"
+        )?;
+        writeln!(f, "|---|---|---|---|")?;
+        for (label, group) in [("Referral", &self.referral), ("Cold", &self.cold)] {
+            writeln!(
+                f,
+                "|{}|{}|{}|{}|",
+                label,
+                group.applied,
+                significance::format_rate(group.interviews, group.applied),
+                significance::format_rate(group.offers, group.applied)
+            )?;
+        }
+        writeln!(
+            f,
+            "|Difference||{}|{}|",
+            p_values(&self.interview_rate),
+            p_values(&self.offer_rate)
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::input_stats_from_csv;
+
+    #[test]
+    fn test_referral_effectiveness() {
+        let input_stats = input_stats_from_csv(
+            "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes
+A,Engineer,20220401,,20220405,20220420,,,yes,,
+B,Engineer,20220401,,20220405,,,,yes,,
+C,Engineer,20220401,20220410,-1,,,,yes,,
+D,Engineer,20220401,20220410,,,,,,,
+E,Engineer,20220401,,,,,,,,
+F,Engineer,20220401,,20220405,,,,,,
+",
+        );
+
+        let effectiveness = ReferralEffectiveness::new(&input_stats);
+        assert_eq!(
+            GroupOutcomes {
+                applied: 3,
+                interviews: 3,
+                offers: 1
+            },
+            effectiveness.referral
+        );
+        assert_eq!(
+            GroupOutcomes {
+                applied: 3,
+                interviews: 1,
+                offers: 0
+            },
+            effectiveness.cold
+        );
+        assert!((effectiveness.interview_rate.fisher_p - 0.4).abs() < 1e-9);
+        assert!(effectiveness.interview_rate.chi_square_unreliable);
+    }
+}
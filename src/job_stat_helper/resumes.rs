@@ -0,0 +1,255 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+
+use super::referrals::{p_values, RateComparison};
+use super::{significance, InputStat};
+
+/// How many days after applying outcomes are counted by default
+pub const DEFAULT_WINDOW_DAYS: i64 = 30;
+
+/// What applications with no `resume_version` are grouped under, they are left out of the
+/// comparisons
+pub const UNVERSIONED: &str = "none";
+
+/// How applications sent with one résumé version went within the window
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionOutcomes {
+    pub version: String,
+    /// the first and last application sent with it, including ones too recent to count
+    pub first_applied: NaiveDate,
+    pub last_applied: NaiveDate,
+    /// applications old enough to have had the whole window to get a response
+    pub applied: u32,
+    pub responses: u32,
+    pub interviews: u32,
+    pub offers: u32,
+}
+
+/// whether `date` came within `window_days` of applying
+fn within(stat: &InputStat, date: Option<NaiveDate>, window_days: i64) -> bool {
+    date.map_or(false, |d| {
+        (d - stat.applied_date()).num_days() <= window_days
+    })
+}
+
+impl VersionOutcomes {
+    fn new(version: &str, input_stats: &[&InputStat], today: NaiveDate, window_days: i64) -> Self {
+        let mut outcomes = Self {
+            version: version.to_string(),
+            first_applied: input_stats.iter().map(|s| s.applied_date()).min().unwrap(),
+            last_applied: input_stats.iter().map(|s| s.applied_date()).max().unwrap(),
+            applied: 0,
+            responses: 0,
+            interviews: 0,
+            offers: 0,
+        };
+
+        for stat in input_stats {
+            if (today - stat.applied_date()).num_days() < window_days {
+                continue;
+            }
+            // an interview i declined has no date, it is counted as coming in time
+            let interviewed = stat.declined_first_interview()
+                || within(stat, stat.first_interview_date(), window_days);
+            let offered = within(stat, stat.offer_date(), window_days);
+
+            outcomes.applied += 1;
+            outcomes.interviews += u32::from(interviewed);
+            outcomes.offers += u32::from(offered);
+            outcomes.responses += u32::from(
+                interviewed || offered || within(stat, stat.rejected_date(), window_days),
+            );
+        }
+
+        outcomes
+    }
+}
+
+/// p-values for whether one version's rates differ from the one used before it
+#[derive(Clone, Debug)]
+pub struct VersionComparison {
+    pub before: String,
+    pub after: String,
+    pub response_rate: RateComparison,
+    pub interview_rate: RateComparison,
+    pub offer_rate: RateComparison,
+}
+
+impl VersionComparison {
+    fn new(before: &VersionOutcomes, after: &VersionOutcomes) -> Self {
+        let compare = |successes: fn(&VersionOutcomes) -> u32| {
+            RateComparison::new(
+                successes(before),
+                before.applied,
+                successes(after),
+                after.applied,
+            )
+        };
+
+        Self {
+            before: before.version.clone(),
+            after: after.version.clone(),
+            response_rate: compare(|o| o.responses),
+            interview_rate: compare(|o| o.interviews),
+            offer_rate: compare(|o| o.offers),
+        }
+    }
+}
+
+/// Response, interview and offer rates of each résumé version, each compared to the version
+/// used before it
+///
+/// versions are used over different date ranges, so a recent version has had less time to get
+/// offers. To control for that only outcomes within `window_days` of applying count and
+/// applications from the last `window_days` are left out
+#[derive(Clone, Debug)]
+pub struct ResumeComparison {
+    pub window_days: i64,
+    /// in the order they were first used
+    pub versions: Vec<VersionOutcomes>,
+    pub comparisons: Vec<VersionComparison>,
+}
+
+impl ResumeComparison {
+    pub fn new(input_stats: &[InputStat], today: NaiveDate, window_days: i64) -> Self {
+        let mut by_version: BTreeMap<&str, Vec<&InputStat>> = BTreeMap::new();
+        for stat in input_stats {
+            let version = stat.resume_version.as_deref().unwrap_or(UNVERSIONED);
+            by_version.entry(version).or_default().push(stat);
+        }
+
+        let mut versions: Vec<VersionOutcomes> = by_version
+            .iter()
+            .map(|(version, stats)| VersionOutcomes::new(version, stats, today, window_days))
+            .collect();
+        versions.sort_by_key(|v| (v.first_applied, v.version.clone()));
+
+        let versioned: Vec<&VersionOutcomes> = versions
+            .iter()
+            .filter(|v| v.version != UNVERSIONED)
+            .collect();
+        let comparisons = versioned
+            .windows(2)
+            .map(|pair| VersionComparison::new(pair[0], pair[1]))
+            .collect();
+
+        Self {
+            window_days,
+            versions,
+            comparisons,
+        }
+    }
+
+    /// whether any version has too few applications for its rates to mean much
+    pub fn has_small_sample(&self) -> bool {
+        self.versions
+            .iter()
+            .any(|v| v.applied < significance::SMALL_SAMPLE_SIZE)
+    }
+}
+
+impl std::fmt::Display for ResumeComparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        writeln!(
+            f,
+            "|Resume version|In use|Jobs applied to|Response rate|Interview rate|Offer rate|"
+        )?;
+        writeln!(f, "|---|---|---|---|---|---|")?;
+        for version in &self.versions {
+            writeln!(
+                f,
+                "|{}|{} to {}|{}|{}|{}|{}|",
+                version.version,
+                version.first_applied,
+                version.last_applied,
+                version.applied,
+                significance::format_rate(version.responses, version.applied),
+                significance::format_rate(version.interviews, version.applied),
+                significance::format_rate(version.offers, version.applied)
+            )?;
+        }
+
+        if !self.comparisons.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "|Change|Response rate|Interview rate|Offer rate|")?;
+            writeln!(f, "|---|---|---|---|")?;
+            for comparison in &self.comparisons {
+                writeln!(
+                    f,
+                    "|{} to {}|{}|{}|{}|",
+                    comparison.before,
+                    comparison.after,
+                    p_values(&comparison.response_rate),
+                    p_values(&comparison.interview_rate),
+                    p_values(&comparison.offer_rate)
+                )?;
+            }
+        }
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "Outcomes within {} days of applying, applications from the last {} days are left out",
+            self.window_days, self.window_days
+        )?;
+        if self.has_small_sample() {
+            writeln!(f, "{}", significance::small_sample_warning())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::input_stats_from_csv;
+
+    const CSV: &str = "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes,resume_version
+A,Engineer,20220301,20220305,,,,,,,,
+B,Engineer,20220401,20220405,,,,,,,,v1
+C,Engineer,20220402,,,,,,,,,v1
+D,Engineer,20220403,,20220420,,,,,,,v1
+E,Engineer,20220501,,20220503,20220520,,,,,,v2
+F,Engineer,20220502,,-1,,,,,,,v2
+G,Engineer,20220503,20220701,,,,,,,,v2
+H,Engineer,20220610,,20220612,,,,,,,v2
+";
+
+    #[test]
+    fn test_resume_comparison() {
+        let today = NaiveDate::from_ymd_opt(2022, 6, 20).unwrap();
+        let comparison = ResumeComparison::new(&input_stats_from_csv(CSV), today, 30);
+
+        let versions: Vec<&str> = comparison
+            .versions
+            .iter()
+            .map(|v| v.version.as_str())
+            .collect();
+        assert_eq!(vec![UNVERSIONED, "v1", "v2"], versions);
+        assert_eq!(
+            VersionOutcomes {
+                version: String::from("v2"),
+                first_applied: NaiveDate::from_ymd_opt(2022, 5, 1).unwrap(),
+                last_applied: NaiveDate::from_ymd_opt(2022, 6, 10).unwrap(),
+                // H is too recent and G was rejected after the window
+                applied: 3,
+                responses: 2,
+                interviews: 2,
+                offers: 1,
+            },
+            comparison.versions[2]
+        );
+        assert_eq!(2, comparison.versions[1].responses);
+
+        assert_eq!(1, comparison.comparisons.len());
+        assert_eq!("v1", comparison.comparisons[0].before);
+        assert!((comparison.comparisons[0].response_rate.fisher_p - 1.0).abs() < 1e-9);
+
+        let rendered = comparison.to_string();
+        assert!(rendered.contains("|v2|2022-05-01 to 2022-06-10|3|2/3 = 66.67%"));
+        assert!(rendered.contains("|v1 to v2|Fisher p=1.0000"));
+    }
+}
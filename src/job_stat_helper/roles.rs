@@ -0,0 +1,281 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use super::{significance, team, InputStat};
+
+/// What a position no rule matches is classified as
+pub const UNCLASSIFIED: &str = "Unclassified";
+
+#[derive(Debug, thiserror::Error)]
+pub enum RoleRulesError {
+    #[error("failed to read the role rules: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the role rules are not valid yaml: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("{0:?} is not a valid regex: {1}")]
+    Regex(String, regex::Error),
+}
+
+/// A position matching `pattern` is classified as `label`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoleRule {
+    pub pattern: String,
+    pub label: String,
+}
+
+/// The rules file, in each list the first rule that matches wins
+/// ```yaml
+/// seniority:
+///   - { pattern: '\b(staff|principal)\b', label: Staff }
+///   - { pattern: '\b(senior|sr)\b', label: Senior }
+/// families:
+///   - { pattern: 'backend|back-end', label: Backend }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoleRulesConfig {
+    #[serde(default)]
+    pub seniority: Vec<RoleRule>,
+    #[serde(default)]
+    pub families: Vec<RoleRule>,
+}
+
+/// The seniority level and role family a position is classified as
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Role {
+    pub seniority: String,
+    pub family: String,
+}
+
+/// Compiled role rules, patterns ignore case
+#[derive(Clone, Debug)]
+pub struct RoleRules {
+    seniority: Vec<(Regex, String)>,
+    families: Vec<(Regex, String)>,
+}
+
+fn compile(rules: Vec<RoleRule>) -> Result<Vec<(Regex, String)>, RoleRulesError> {
+    rules
+        .into_iter()
+        .map(|rule| {
+            match RegexBuilder::new(&rule.pattern)
+                .case_insensitive(true)
+                .build()
+            {
+                Ok(regex) => Ok((regex, rule.label)),
+                Err(e) => Err(RoleRulesError::Regex(rule.pattern, e)),
+            }
+        })
+        .collect()
+}
+
+fn first_match(rules: &[(Regex, String)], position: &str) -> String {
+    rules
+        .iter()
+        .find(|(regex, _)| regex.is_match(position))
+        .map_or_else(|| String::from(UNCLASSIFIED), |(_, label)| label.clone())
+}
+
+impl RoleRules {
+    pub fn new(config: RoleRulesConfig) -> Result<Self, RoleRulesError> {
+        Ok(Self {
+            seniority: compile(config.seniority)?,
+            families: compile(config.families)?,
+        })
+    }
+
+    pub fn classify(&self, position: &str) -> Role {
+        Role {
+            seniority: first_match(&self.seniority, position),
+            family: first_match(&self.families, position),
+        }
+    }
+}
+
+pub fn load_role_rules(path: &Path) -> Result<RoleRules, RoleRulesError> {
+    RoleRules::new(serde_yaml::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+/// How applications to one group of roles went
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GroupRates {
+    pub label: String,
+    pub applied: u32,
+    pub responses: u32,
+    pub first_interviews: u32,
+    pub offers: u32,
+}
+
+impl GroupRates {
+    fn offer_rate(&self) -> f64 {
+        f64::from(self.offers) / f64::from(self.applied)
+    }
+
+    fn response_rate(&self) -> f64 {
+        f64::from(self.responses) / f64::from(self.applied)
+    }
+}
+
+/// rows grouped by the labels `labels` gives them, a row with several labels is counted in each
+/// best offer rate first, ties broken by response rate
+pub(super) fn group_by<L: IntoIterator<Item = String>>(
+    input_stats: &[InputStat],
+    labels: impl Fn(&InputStat) -> L,
+) -> Vec<GroupRates> {
+    let mut groups: BTreeMap<String, GroupRates> = BTreeMap::new();
+    for stat in input_stats {
+        for label in labels(stat) {
+            let rates = groups.entry(label.clone()).or_insert(GroupRates {
+                label,
+                applied: 0,
+                responses: 0,
+                first_interviews: 0,
+                offers: 0,
+            });
+            rates.applied += 1;
+            rates.responses += u32::from(team::responded(stat));
+            rates.first_interviews += u32::from(stat.first_interview_date().is_some());
+            rates.offers += u32::from(stat.offer_dt.is_some());
+        }
+    }
+
+    let mut groups: Vec<GroupRates> = groups.into_values().collect();
+    groups.sort_by(|a, b| {
+        b.offer_rate()
+            .total_cmp(&a.offer_rate())
+            .then(b.response_rate().total_cmp(&a.response_rate()))
+    });
+    groups
+}
+
+/// Response, first interview and offer rates by seniority level and by role family
+#[derive(Clone, Debug)]
+pub struct RoleBreakdown {
+    pub by_seniority: Vec<GroupRates>,
+    pub by_family: Vec<GroupRates>,
+}
+
+impl RoleBreakdown {
+    pub fn new(input_stats: &[InputStat], rules: &RoleRules) -> Self {
+        Self {
+            by_seniority: group_by(input_stats, |s| [rules.classify(&s.position).seniority]),
+            by_family: group_by(input_stats, |s| [rules.classify(&s.position).family]),
+        }
+    }
+}
+
+pub(super) fn write_table(
+    f: &mut std::fmt::Formatter,
+    heading: &str,
+    groups: &[GroupRates],
+) -> std::fmt::Result {
+    writeln!(
+        f,
+        "|{}|Jobs applied to|Response rate|First interview rate|Offer rate|",
+        heading
+    )?;
+    writeln!(f, "|---|---|---|---|---|")?;
+    for group in groups {
+        writeln!(
+            f,
+            "|{}|{}|{}|{}|{}|",
+            group.label,
+            group.applied,
+            significance::format_rate(group.responses, group.applied),
+            significance::format_rate(group.first_interviews, group.applied),
+            significance::format_rate(group.offers, group.applied)
+        )?;
+    }
+
+    Ok(())
+}
+
+impl std::fmt::Display for RoleBreakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write_table(f, "Seniority", &self.by_seniority)?;
+        writeln!(f)?;
+        write_table(f, "Role family", &self.by_family)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::input_stats_from_csv;
+
+    const RULES: &str = r#"
+seniority:
+  - { pattern: '\b(staff|principal)\b', label: Staff }
+  - { pattern: '\b(senior|sr)\b', label: Senior }
+  - { pattern: '\b(junior|jr)\b', label: Junior }
+families:
+  - { pattern: 'backend|back-end', label: Backend }
+  - { pattern: 'frontend|front-end', label: Frontend }
+  - { pattern: '\bdata\b', label: Data }
+"#;
+
+    fn rules() -> RoleRules {
+        RoleRules::new(serde_yaml::from_str(RULES).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_classify() {
+        let rules = rules();
+        assert_eq!(
+            Role {
+                seniority: String::from("Staff"),
+                family: String::from("Backend"),
+            },
+            // staff comes before senior in the rules
+            rules.classify("Senior Staff Back-End Engineer")
+        );
+        assert_eq!("Senior", rules.classify("Sr. Data Engineer").seniority);
+        assert_eq!(UNCLASSIFIED, rules.classify("Engineer").seniority);
+        assert_eq!(UNCLASSIFIED, rules.classify("Database Admin").family);
+
+        let invalid = RoleRulesConfig {
+            seniority: vec![RoleRule {
+                pattern: String::from("(senior"),
+                label: String::from("Senior"),
+            }],
+            families: vec![],
+        };
+        assert!(matches!(
+            RoleRules::new(invalid),
+            Err(RoleRulesError::Regex(..))
+        ));
+    }
+
+    #[test]
+    fn test_role_breakdown() {
+        let input_stats = input_stats_from_csv(
+            "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes
+A,Senior Backend Engineer,20220401,,20220405,20220420,100000,,,,
+B,Senior Frontend Engineer,20220401,20220410,,,,,,,
+C,Backend Engineer,20220401,,,,,,,,
+D,Junior Frontend Developer,20220401,,-1,,,,,,
+",
+        );
+        let breakdown = RoleBreakdown::new(&input_stats, &rules());
+
+        assert_eq!("Senior", breakdown.by_seniority[0].label);
+        assert_eq!(2, breakdown.by_seniority[0].applied);
+        assert_eq!(
+            GroupRates {
+                label: String::from("Frontend"),
+                applied: 2,
+                responses: 2,
+                first_interviews: 0,
+                offers: 0,
+            },
+            breakdown.by_family[1]
+        );
+
+        let rendered = breakdown.to_string();
+        assert!(rendered.contains("|Backend|2|1/2 = 50.00%"));
+        assert!(rendered.contains("|Unclassified|1|0/1 = 0.00%"));
+    }
+}
@@ -0,0 +1,418 @@
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+
+use chrono::NaiveDate;
+use rhai::{Array, Dynamic, Engine, FnPtr, AST};
+
+use super::metrics::{Metric, MetricRegistry};
+use super::{display_or_na, significance, InputStat};
+
+/// How many operations one run of the script or one call of its closures may take, so a
+/// script that never finishes fails instead of hanging the report
+pub const MAX_SCRIPT_OPERATIONS: u64 = 1_000_000;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ScriptError {
+    #[error("failed to read the script: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("the script does not compile: {0}")]
+    Compile(String),
+    #[error("the script failed: {0}")]
+    Runtime(String),
+}
+
+/// What the top level of a script asked for, in the order it asked
+enum Definition {
+    Filter(FnPtr),
+    Count(String, FnPtr),
+    Share(String, FnPtr, FnPtr),
+    Metric(String, FnPtr),
+}
+
+/// A compiled script with the filters and metrics it defined
+///
+/// the top level of the script runs once when it is compiled and defines everything with
+/// ```text
+/// filter(|app| app.position.contains("Rust"));
+/// count("Referred", |app| app.channel == "referral");
+/// share("Responded within 5 days", |app| app.days_to_response <= 5, |app| true);
+/// metric("Offers over 100k", |apps| apps.filter(|app| app.offer_amt > 100000.0).len());
+/// ```
+/// every application is an object with the columns of the input as properties, dates as
+/// `YYYY-MM-DD` strings, and `days_to_first_interview`, `days_to_rejection`, `days_to_offer`
+/// and `days_to_response` counted from the application. The tags in its notes are
+/// `app.hashtags` and `app.tag("key")`. A missing value is `()`
+pub struct Script {
+    engine: Rc<Engine>,
+    ast: Rc<AST>,
+    definitions: Vec<Definition>,
+}
+
+/// Reads and compiles a script file, see `Script`
+pub fn load_script(path: &Path) -> Result<Script, ScriptError> {
+    Script::compile(&std::fs::read_to_string(path)?)
+}
+
+impl Script {
+    pub fn compile(source: &str) -> Result<Self, ScriptError> {
+        let definitions = Rc::new(RefCell::new(vec![]));
+        let mut engine = new_engine();
+
+        let defined = definitions.clone();
+        engine.register_fn("filter", move |keep: FnPtr| {
+            defined.borrow_mut().push(Definition::Filter(keep))
+        });
+        let defined = definitions.clone();
+        engine.register_fn("count", move |name: &str, counts: FnPtr| {
+            defined
+                .borrow_mut()
+                .push(Definition::Count(name.to_string(), counts))
+        });
+        let defined = definitions.clone();
+        engine.register_fn("share", move |name: &str, part: FnPtr, whole: FnPtr| {
+            defined
+                .borrow_mut()
+                .push(Definition::Share(name.to_string(), part, whole))
+        });
+        let defined = definitions.clone();
+        engine.register_fn("metric", move |name: &str, compute: FnPtr| {
+            defined
+                .borrow_mut()
+                .push(Definition::Metric(name.to_string(), compute))
+        });
+
+        let ast = engine
+            .compile(source)
+            .map_err(|e| ScriptError::Compile(e.to_string()))?;
+        engine
+            .run_ast(&ast)
+            .map_err(|e| ScriptError::Runtime(e.to_string()))?;
+
+        let definitions = definitions.take();
+        Ok(Self {
+            engine: Rc::new(engine),
+            ast: Rc::new(ast),
+            definitions,
+        })
+    }
+
+    /// The applications every filter of the script keeps, all of them without filters
+    pub fn filter(&self, input_stats: &[InputStat]) -> Result<Vec<InputStat>, ScriptError> {
+        let mut kept = vec![];
+        for stat in input_stats {
+            let mut keep = true;
+            for definition in &self.definitions {
+                if let Definition::Filter(f) = definition {
+                    keep = keep && call_on(&self.engine, &self.ast, f, stat)?;
+                }
+            }
+            if keep {
+                kept.push(stat.clone());
+            }
+        }
+
+        Ok(kept)
+    }
+
+    /// Adds the script's metrics after the ones already in the registry, a metric named like an
+    /// existing one replaces it
+    pub fn register_metrics(&self, registry: &mut MetricRegistry) {
+        for definition in &self.definitions {
+            let (name, kind) = match definition {
+                Definition::Filter(_) => continue,
+                Definition::Count(name, counts) => (name, ScriptMetricKind::Count(counts.clone())),
+                Definition::Share(name, part, whole) => {
+                    (name, ScriptMetricKind::Share(part.clone(), whole.clone()))
+                }
+                Definition::Metric(name, compute) => {
+                    (name, ScriptMetricKind::Metric(compute.clone()))
+                }
+            };
+            registry.register(Box::new(ScriptMetric {
+                name: name.clone(),
+                kind,
+                engine: self.engine.clone(),
+                ast: self.ast.clone(),
+                formatted: RefCell::new(None),
+            }));
+        }
+    }
+}
+
+fn new_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(MAX_SCRIPT_OPERATIONS)
+        .set_max_expr_depths(64, 32)
+        .register_type_with_name::<InputStat>("Application")
+        .register_get("company", |s: &mut InputStat| s.company.clone())
+        .register_get("position", |s: &mut InputStat| s.position.clone())
+        .register_get("applied", |s: &mut InputStat| s.applied_date().to_string())
+        .register_get("rejected", |s: &mut InputStat| {
+            date_or_unit(s.rejected_date())
+        })
+        .register_get("first_interview", |s: &mut InputStat| {
+            date_or_unit(s.first_interview_date())
+        })
+        .register_get("declined_first_interview", |s: &mut InputStat| {
+            s.declined_first_interview()
+        })
+        .register_get("offer", |s: &mut InputStat| date_or_unit(s.offer_date()))
+        .register_get("offer_amt", |s: &mut InputStat| or_unit(s.offer_amt))
+        .register_get("currency", |s: &mut InputStat| or_unit(s.currency.clone()))
+        .register_get("accepted", |s: &mut InputStat| or_unit(s.accepted.clone()))
+        .register_get("decision", |s: &mut InputStat| {
+            date_or_unit(s.decision_date())
+        })
+        .register_get("referral", |s: &mut InputStat| or_unit(s.referral.clone()))
+        .register_get("url", |s: &mut InputStat| or_unit(s.url.clone()))
+        .register_get("notes", |s: &mut InputStat| or_unit(s.notes.clone()))
+        .register_get("owner", |s: &mut InputStat| or_unit(s.owner.clone()))
+        .register_get("resume_version", |s: &mut InputStat| {
+            or_unit(s.resume_version.clone())
+        })
+        .register_get("hashtags", |s: &mut InputStat| {
+            s.tags()
+                .hashtags
+                .into_iter()
+                .map(Dynamic::from)
+                .collect::<Array>()
+        })
+        .register_fn("tag", |s: &mut InputStat, key: &str| {
+            or_unit(s.tags().pairs.remove(&key.to_lowercase()))
+        })
+        .register_get("channel", |s: &mut InputStat| {
+            or_unit(s.channel().and_then(Result::ok).map(|c| c.to_string()))
+        })
+        .register_get("days_to_first_interview", |s: &mut InputStat| {
+            days_since_applied(s, s.first_interview_date())
+        })
+        .register_get("days_to_rejection", |s: &mut InputStat| {
+            days_since_applied(s, s.rejected_date())
+        })
+        .register_get("days_to_offer", |s: &mut InputStat| {
+            days_since_applied(s, s.offer_date())
+        })
+        .register_get("days_to_response", |s: &mut InputStat| {
+            let first = [s.first_interview_date(), s.rejected_date(), s.offer_date()]
+                .into_iter()
+                .flatten()
+                .min();
+            days_since_applied(s, first)
+        });
+
+    engine
+}
+
+fn or_unit<T: Into<Dynamic>>(value: Option<T>) -> Dynamic {
+    value.map_or(Dynamic::UNIT, Into::into)
+}
+
+fn date_or_unit(date: Option<NaiveDate>) -> Dynamic {
+    or_unit(date.map(|d| d.to_string()))
+}
+
+fn days_since_applied(stat: &InputStat, date: Option<NaiveDate>) -> Dynamic {
+    or_unit(date.map(|d| (d - stat.applied_date()).num_days()))
+}
+
+/// calls a `|app| ...` closure of the script that must return true or false
+fn call_on(engine: &Engine, ast: &AST, f: &FnPtr, stat: &InputStat) -> Result<bool, ScriptError> {
+    f.call::<bool>(engine, ast, (stat.clone(),))
+        .map_err(|e| ScriptError::Runtime(e.to_string()))
+}
+
+enum ScriptMetricKind {
+    Count(FnPtr),
+    Share(FnPtr, FnPtr),
+    Metric(FnPtr),
+}
+
+/// A metric defined by a script, shown as `error: ...` in the report when the script fails on
+/// the input
+struct ScriptMetric {
+    name: String,
+    kind: ScriptMetricKind,
+    engine: Rc<Engine>,
+    ast: Rc<AST>,
+    /// how the value last computed is shown, so formatting does not run the script again
+    formatted: RefCell<Option<String>>,
+}
+
+impl ScriptMetric {
+    fn count(&self, input_stats: &[InputStat], f: &FnPtr) -> Result<u32, ScriptError> {
+        let mut count = 0;
+        for stat in input_stats {
+            if call_on(&self.engine, &self.ast, f, stat)? {
+                count += 1;
+            }
+        }
+        Ok(count)
+    }
+
+    /// the value with how it is shown
+    fn evaluate(&self, input_stats: &[InputStat]) -> Result<(Option<f64>, String), ScriptError> {
+        match &self.kind {
+            ScriptMetricKind::Count(counts) => {
+                let count = self.count(input_stats, counts)?;
+                Ok((Some(count.into()), count.to_string()))
+            }
+            ScriptMetricKind::Share(part, whole) => {
+                let (mut parts, mut wholes) = (0, 0);
+                for stat in input_stats {
+                    if call_on(&self.engine, &self.ast, whole, stat)? {
+                        wholes += 1;
+                        if call_on(&self.engine, &self.ast, part, stat)? {
+                            parts += 1;
+                        }
+                    }
+                }
+                let value = if wholes == 0 {
+                    None
+                } else {
+                    Some(f64::from(parts) / f64::from(wholes))
+                };
+                Ok((value, significance::format_rate(parts, wholes)))
+            }
+            ScriptMetricKind::Metric(compute) => {
+                let apps: Array = input_stats.iter().cloned().map(Dynamic::from).collect();
+                let result = compute
+                    .call::<Dynamic>(&self.engine, &self.ast, (apps,))
+                    .map_err(|e| ScriptError::Runtime(e.to_string()))?;
+                let value = if result.is_unit() {
+                    None
+                } else if let Ok(f) = result.as_float() {
+                    Some(f)
+                } else if let Ok(i) = result.as_int() {
+                    Some(i as f64)
+                } else {
+                    return Err(ScriptError::Runtime(format!(
+                        "{} returned a {} instead of a number",
+                        self.name,
+                        result.type_name()
+                    )));
+                };
+                Ok((value, display_or_na(value)))
+            }
+        }
+    }
+}
+
+impl Metric for ScriptMetric {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn compute(&self, input_stats: &[InputStat]) -> Option<f64> {
+        let (value, formatted) = match self.evaluate(input_stats) {
+            Ok(evaluated) => evaluated,
+            Err(e) => (None, format!("error: {}", e)),
+        };
+        self.formatted.replace(Some(formatted));
+        value
+    }
+
+    /// a rate is shown with its counts and an error with its message, both kept by `compute`
+    fn format(&self, value: Option<f64>, input_stats: &[InputStat]) -> String {
+        match self.formatted.take() {
+            Some(formatted) => formatted,
+            None => {
+                self.compute(input_stats);
+                self.formatted
+                    .take()
+                    .unwrap_or_else(|| display_or_na(value))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::{input_stats_from_csv, team, JobStats};
+
+    const CSV: &str = "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes
+A,Rust Engineer,20220401,,20220404,20220420,120000,,yes,,
+B,Rust Engineer,20220401,20220410,,,,,,,
+C,Rust Developer,20220401,,,,,,,,
+D,Go Engineer,20220401,20220403,,,,,,,
+";
+
+    const SCRIPT: &str = r#"
+filter(|app| app.position.contains("Engineer") && !app.hashtags.contains("skip"));
+count("Referred", |app| app.channel == "referral");
+share("Responded within 5 days", |app| app.days_to_response <= 5, |app| app.position.contains("Rust"));
+metric("Offers over 100k", |apps| apps.filter(|app| app.offer_amt > 100000.0).len());
+metric("Mean days to response", |apps| {
+    let days = apps.map(|app| app.days_to_response).filter(|d| d != ());
+    if days.is_empty() { return (); }
+    days.reduce(|sum, d| sum + d, 0) / days.len()
+});
+"#;
+
+    #[test]
+    fn test_script_filters_and_metrics() {
+        let script = Script::compile(SCRIPT).unwrap();
+        let input_stats = script.filter(&input_stats_from_csv(CSV)).unwrap();
+        assert_eq!(3, input_stats.len());
+
+        let mut registry = MetricRegistry::builtin();
+        script.register_metrics(&mut registry);
+        let job_stats = JobStats::with_registry(input_stats, &registry);
+
+        assert_eq!(Some(3.0), job_stats.value("Jobs applied to"));
+        assert_eq!(Some(1.0), job_stats.value("Referred"));
+        assert_eq!(Some(0.5), job_stats.value("Responded within 5 days"));
+        assert_eq!(Some(1.0), job_stats.value("Offers over 100k"));
+        // 3, 9 and 2 days
+        assert_eq!(Some(4.0), job_stats.value("Mean days to response"));
+
+        let rendered = job_stats.to_string();
+        assert!(rendered.contains("|Referred|1|"));
+
+        let script = Script::compile(r#"filter(|app| app.owner == "alice");"#).unwrap();
+        let mut input_stats = team::with_owner(input_stats_from_csv(CSV), "alice");
+        input_stats[0].owner = Some(String::from("bob"));
+        assert_eq!(3, script.filter(&input_stats).unwrap().len());
+        assert!(rendered.contains("|Responded within 5 days|1/2 = 50.00%"));
+    }
+
+    #[test]
+    fn test_script_errors() {
+        assert!(matches!(
+            Script::compile("count(\"Broken\", |app| "),
+            Err(ScriptError::Compile(_))
+        ));
+        assert!(matches!(
+            Script::compile("throw \"nope\";"),
+            Err(ScriptError::Runtime(_))
+        ));
+
+        let script = Script::compile(r#"metric("Not a number", |apps| "many");"#).unwrap();
+        let mut registry = MetricRegistry::empty();
+        script.register_metrics(&mut registry);
+        let job_stats = JobStats::with_registry(input_stats_from_csv(CSV), &registry);
+        assert_eq!(None, job_stats.value("Not a number"));
+        assert!(job_stats
+            .to_string()
+            .contains("|Not a number|error: the script failed: Not a number returned a string"));
+
+        assert!(matches!(
+            Script::compile("loop {}"),
+            Err(ScriptError::Runtime(_))
+        ));
+        let script = Script::compile(r#"metric("Forever", |apps| { loop {} });"#).unwrap();
+        let mut registry = MetricRegistry::empty();
+        script.register_metrics(&mut registry);
+        let job_stats = JobStats::with_registry(input_stats_from_csv(CSV), &registry);
+        assert!(job_stats.to_string().contains("|Forever|error: "));
+
+        let script = Script::compile("filter(|app| app.company);").unwrap();
+        assert!(matches!(
+            script.filter(&input_stats_from_csv(CSV)),
+            Err(ScriptError::Runtime(_))
+        ));
+    }
+}
@@ -0,0 +1,197 @@
+/// z score for a two sided 95% interval
+pub const Z_95: f64 = 1.959_963_984_540_054;
+
+/// rates with fewer trials than this are flagged as too small to mean much
+pub const SMALL_SAMPLE_SIZE: u32 = 10;
+
+/// explains the `*` on a rate, the trials are whatever the rate is out of, applications or
+/// rejections
+pub fn small_sample_warning() -> String {
+    format!(
+        "* fewer than {} trials behind this rate, the applications or rejections it is out of, the interval is too wide to mean much",
+        SMALL_SAMPLE_SIZE
+    )
+}
+
+/// Wilson score interval for a binomial proportion
+/// returns `None` when there were no trials or more successes than trials
+pub fn wilson_interval(successes: u32, trials: u32, z: f64) -> Option<(f64, f64)> {
+    if trials == 0 || successes > trials {
+        return None;
+    }
+
+    let n = trials as f64;
+    let p = successes as f64 / n;
+    let z2 = z * z;
+    let denominator = 1.0 + z2 / n;
+    let center = (p + z2 / (2.0 * n)) / denominator;
+    let margin = (z / denominator) * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt();
+
+    Some(((center - margin).max(0.0), (center + margin).min(1.0)))
+}
+
+/// formats a rate with its raw counts and 95% wilson interval e.g. `1/4 = 25.00% [4.56%, 69.94%]*`
+/// the trailing `*` marks a rate with fewer than `SMALL_SAMPLE_SIZE` trials
+/// a count that is not a subset of the trials, e.g. `5/2`, has no rate and is shown as n/a
+pub fn format_rate(successes: u32, trials: u32) -> String {
+    let marker = if trials < SMALL_SAMPLE_SIZE { "*" } else { "" };
+
+    match wilson_interval(successes, trials, Z_95) {
+        Some((low, high)) => format!(
+            "{}/{} = {} [{:.2}%, {:.2}%]{}",
+            successes,
+            trials,
+            super::percent_with_precision_2(successes as f64, trials as f64),
+            low * 100.0,
+            high * 100.0,
+            marker
+        ),
+        None => format!("{}/{} = n/a{}", successes, trials, marker),
+    }
+}
+
+/// Two sided Fisher exact test p-value for the 2x2 table
+/// ```text
+/// | a | b |
+/// | c | d |
+/// ```
+pub fn fisher_exact(a: u32, b: u32, c: u32, d: u32) -> f64 {
+    let row_1 = a + b;
+    let col_1 = a + c;
+    let n = a + b + c + d;
+
+    // the table is fixed by its margins and the top left cell
+    let min_a = col_1.saturating_sub(c + d);
+    let max_a = row_1.min(col_1);
+
+    let observed = hypergeometric_ln_p(a, row_1, col_1, n);
+    // tolerance so tables as likely as the observed one are not lost to rounding
+    let cutoff = observed + 1e-7;
+
+    (min_a..=max_a)
+        .map(|x| hypergeometric_ln_p(x, row_1, col_1, n))
+        .filter(|ln_p| *ln_p <= cutoff)
+        .map(f64::exp)
+        .sum::<f64>()
+        .min(1.0)
+}
+
+/// Pearson chi-square test (1 degree of freedom) on a 2x2 table
+/// returns the statistic and its p-value, `None` if a row or column is empty
+pub fn chi_square_2x2(a: u32, b: u32, c: u32, d: u32) -> Option<(f64, f64)> {
+    let (a, b, c, d) = (a as f64, b as f64, c as f64, d as f64);
+    let n = a + b + c + d;
+    let margins = (a + b) * (c + d) * (a + c) * (b + d);
+    if margins == 0.0 {
+        return None;
+    }
+
+    let statistic = n * (a * d - b * c).powi(2) / margins;
+    Some((statistic, erfc((statistic / 2.0).sqrt())))
+}
+
+/// whether any expected cell count of the 2x2 table is under 5, where chi-square is unreliable
+pub fn chi_square_unreliable(a: u32, b: u32, c: u32, d: u32) -> bool {
+    let n = (a + b + c + d) as f64;
+    if n == 0.0 {
+        return true;
+    }
+    let rows = [(a + b) as f64, (c + d) as f64];
+    let cols = [(a + c) as f64, (b + d) as f64];
+
+    rows.iter()
+        .any(|row| cols.iter().any(|col| row * col / n < 5.0))
+}
+
+/// ln of the probability of seeing `x` successes in the top left cell given the table margins
+fn hypergeometric_ln_p(x: u32, row_1: u32, col_1: u32, n: u32) -> f64 {
+    ln_choose(col_1, x) + ln_choose(n - col_1, row_1 - x) - ln_choose(n, row_1)
+}
+
+fn ln_choose(n: u32, k: u32) -> f64 {
+    ln_factorial(n) - ln_factorial(k) - ln_factorial(n - k)
+}
+
+fn ln_factorial(n: u32) -> f64 {
+    (2..=n).map(|i| (i as f64).ln()).sum()
+}
+
+/// Complementary error function (Numerical Recipes' Chebyshev fit, accurate to about 1.2e-7)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t
+        * (-z * z - 1.265_512_23
+            + t * (1.000_023_68
+                + t * (0.374_091_96
+                    + t * (0.096_784_18
+                        + t * (-0.186_288_06
+                            + t * (0.278_868_07
+                                + t * (-1.135_203_98
+                                    + t * (1.488_515_87
+                                        + t * (-0.822_152_23 + t * 0.170_872_77)))))))))
+            .exp();
+
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_close(expected: f64, actual: f64) {
+        assert!(
+            (expected - actual).abs() < 1e-4,
+            "expected {expected} got {actual}"
+        );
+    }
+
+    #[test]
+    fn test_wilson_interval() {
+        assert_eq!(None, wilson_interval(0, 0, Z_95));
+        assert_eq!(None, wilson_interval(5, 2, Z_95));
+
+        let (low, high) = wilson_interval(1, 4, Z_95).unwrap();
+        assert_close(0.0456, low);
+        assert_close(0.6994, high);
+
+        let (low, high) = wilson_interval(0, 10, Z_95).unwrap();
+        assert_eq!(0.0, low);
+        assert_close(0.2775, high);
+    }
+
+    #[test]
+    fn test_format_rate() {
+        assert_eq!("1/4 = 25.00% [4.56%, 69.94%]*", format_rate(1, 4));
+        assert_eq!("0/0 = n/a*", format_rate(0, 0));
+        assert_eq!("5/2 = n/a*", format_rate(5, 2));
+        assert_eq!("5/10 = 50.00% [23.66%, 76.34%]", format_rate(5, 10));
+    }
+
+    #[test]
+    fn test_small_sample_warning() {
+        assert!(small_sample_warning().starts_with("* fewer than 10 trials"));
+    }
+
+    #[test]
+    fn test_fisher_exact() {
+        // the lady tasting tea
+        assert_close(0.4857, fisher_exact(3, 1, 1, 3));
+        assert_close(0.0028, fisher_exact(1, 9, 11, 3));
+        assert_close(1.0, fisher_exact(2, 2, 2, 2));
+    }
+
+    #[test]
+    fn test_chi_square_2x2() {
+        let (statistic, p) = chi_square_2x2(20, 10, 10, 20).unwrap();
+        assert_close(6.6667, statistic);
+        assert_close(0.0098, p);
+        assert_eq!(None, chi_square_2x2(0, 0, 3, 4));
+        assert!(chi_square_unreliable(3, 1, 1, 3));
+        assert!(!chi_square_unreliable(20, 10, 10, 20));
+    }
+}
@@ -0,0 +1,302 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::metrics::{Metric, MetricRegistry};
+use super::roles::{group_by, write_table, GroupRates};
+use super::{significance, InputStat};
+
+/// The annotations written in an application's notes, lowercased
+/// e.g. `#remote #contract level:senior` has the hashtags `remote` and `contract` and the pair
+/// `level` = `senior`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Tags {
+    pub hashtags: BTreeSet<String>,
+    pub pairs: BTreeMap<String, String>,
+}
+
+fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+/// Parses hashtags and `key:value` pairs out of free text
+/// a key starts with a letter so times like `10:30` are not pairs, and urls are skipped
+pub fn parse_tags(notes: &str) -> Tags {
+    let mut tags = Tags::default();
+    for word in notes.split_whitespace() {
+        let word = word.trim_end_matches(|c: char| c.is_ascii_punctuation() && c != '#');
+        if let Some(hashtag) = word.strip_prefix('#') {
+            if !hashtag.is_empty() && hashtag.chars().all(is_tag_char) {
+                tags.hashtags.insert(hashtag.to_lowercase());
+            }
+        } else if let Some((key, value)) = word.split_once(':') {
+            let is_key =
+                key.starts_with(|c: char| c.is_alphabetic()) && key.chars().all(is_tag_char);
+            if is_key && !value.is_empty() && !value.starts_with("//") {
+                tags.pairs.insert(key.to_lowercase(), value.to_lowercase());
+            }
+        }
+    }
+
+    tags
+}
+
+/// A condition on an application's tags
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TagFilter {
+    /// `#remote`
+    Hashtag(String),
+    /// `level:senior`
+    Pair(String, String),
+    /// `level`, any value
+    Key(String),
+}
+
+impl std::str::FromStr for TagFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        if let Some(hashtag) = s.strip_prefix('#') {
+            return match hashtag {
+                "" => Err(String::from("a hashtag filter needs a tag after the #")),
+                hashtag => Ok(TagFilter::Hashtag(hashtag.to_string())),
+            };
+        }
+        match s.split_once(':') {
+            Some((key, value)) if !key.is_empty() && !value.is_empty() => {
+                Ok(TagFilter::Pair(key.to_string(), value.to_string()))
+            }
+            Some(_) => Err(format!("{s} is not a key:value tag")),
+            None if s.is_empty() => Err(String::from("an empty tag filter matches nothing")),
+            None => Ok(TagFilter::Key(s)),
+        }
+    }
+}
+
+impl TagFilter {
+    pub fn matches(&self, tags: &Tags) -> bool {
+        match self {
+            TagFilter::Hashtag(hashtag) => tags.hashtags.contains(hashtag),
+            TagFilter::Pair(key, value) => tags.pairs.get(key) == Some(value),
+            TagFilter::Key(key) => tags.pairs.contains_key(key),
+        }
+    }
+}
+
+/// The applications that match every filter
+pub fn filter_by_tags(input_stats: &[InputStat], filters: &[TagFilter]) -> Vec<InputStat> {
+    input_stats
+        .iter()
+        .filter(|stat| {
+            let tags = stat.tags();
+            filters.iter().all(|f| f.matches(&tags))
+        })
+        .cloned()
+        .collect()
+}
+
+/// The applications whose notes contain every word of `query`, ignoring case
+pub fn search(input_stats: &[InputStat], query: &str) -> Vec<InputStat> {
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    input_stats
+        .iter()
+        .filter(|stat| {
+            let notes = stat.notes.as_deref().unwrap_or_default().to_lowercase();
+            words.iter().all(|w| notes.contains(w.as_str()))
+        })
+        .cloned()
+        .collect()
+}
+
+/// What applications are grouped by in a `TagBreakdown`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TagGroup {
+    /// each hashtag is a group, an application with several is in each of them
+    Hashtags,
+    /// each value of the key is a group
+    Key(String),
+}
+
+impl std::str::FromStr for TagGroup {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "" => Err(String::from("expected hashtags or a tag key to group by")),
+            "hashtags" | "#" => Ok(TagGroup::Hashtags),
+            key => Ok(TagGroup::Key(key.to_string())),
+        }
+    }
+}
+
+/// What applications with none of the tags grouped by are grouped under
+pub const UNTAGGED: &str = "untagged";
+
+/// Response, first interview and offer rates by tag
+#[derive(Clone, Debug)]
+pub struct TagBreakdown {
+    pub group: TagGroup,
+    pub groups: Vec<GroupRates>,
+}
+
+impl TagBreakdown {
+    pub fn new(input_stats: &[InputStat], group: TagGroup) -> Self {
+        let groups = group_by(input_stats, |stat| {
+            let tags = stat.tags();
+            let labels: Vec<String> = match &group {
+                TagGroup::Hashtags => tags.hashtags.iter().map(|h| format!("#{}", h)).collect(),
+                TagGroup::Key(key) => tags.pairs.get(key).cloned().into_iter().collect(),
+            };
+            if labels.is_empty() {
+                vec![String::from(UNTAGGED)]
+            } else {
+                labels
+            }
+        });
+
+        Self { group, groups }
+    }
+}
+
+impl std::fmt::Display for TagBreakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        let heading = match &self.group {
+            TagGroup::Hashtags => String::from("Hashtag"),
+            TagGroup::Key(key) => key.clone(),
+        };
+        write_table(f, &heading, &self.groups)
+    }
+}
+
+/// How many applications have a tag, out of all of them
+struct TagMetric {
+    name: String,
+    filter: TagFilter,
+}
+
+impl TagMetric {
+    fn count(&self, input_stats: &[InputStat]) -> u32 {
+        input_stats
+            .iter()
+            .filter(|s| self.filter.matches(&s.tags()))
+            .count() as u32
+    }
+}
+
+impl Metric for TagMetric {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn compute(&self, input_stats: &[InputStat]) -> Option<f64> {
+        Some(self.count(input_stats).into())
+    }
+
+    fn format(&self, value: Option<f64>, input_stats: &[InputStat]) -> String {
+        let count = value.unwrap_or_default() as u32;
+        format!(
+            "{} ({} of all jobs applied to)",
+            count,
+            significance::format_rate(count, input_stats.len() as u32)
+        )
+    }
+}
+
+/// Adds a count of every hashtag and `key:value` pair used in the notes to the registry, named
+/// e.g. `Tagged #remote`
+pub fn register_tag_metrics(input_stats: &[InputStat], registry: &mut MetricRegistry) {
+    let mut filters = BTreeSet::new();
+    for stat in input_stats {
+        let tags = stat.tags();
+        for hashtag in tags.hashtags {
+            filters.insert((format!("#{}", hashtag), TagFilter::Hashtag(hashtag)));
+        }
+        for (key, value) in tags.pairs {
+            filters.insert((format!("{}:{}", key, value), TagFilter::Pair(key, value)));
+        }
+    }
+
+    for (label, filter) in filters {
+        registry.register(Box::new(TagMetric {
+            name: format!("Tagged {}", label),
+            filter,
+        }));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::{input_stats_from_csv, JobStats};
+
+    const CSV: &str = "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes
+A,Engineer,20220401,,20220405,20220420,,,,,#remote #Contract level:senior
+B,Engineer,20220401,20220410,,,,,,,\"#remote, recruiter said level:Mid at 10:30\"
+C,Engineer,20220401,,,,,,,,see https://example.com/job #onsite
+D,Engineer,20220401,,,,,,,,
+";
+
+    #[test]
+    fn test_parse_tags() {
+        let tags = parse_tags("#remote #Contract, level:senior at 10:30 see https://x.example #");
+        assert_eq!(
+            BTreeSet::from([String::from("contract"), String::from("remote")]),
+            tags.hashtags
+        );
+        assert_eq!(
+            BTreeMap::from([(String::from("level"), String::from("senior"))]),
+            tags.pairs
+        );
+    }
+
+    #[test]
+    fn test_filter_and_search() {
+        let input_stats = input_stats_from_csv(CSV);
+        let filters: Vec<TagFilter> = ["#remote", "level"]
+            .iter()
+            .map(|f| f.parse().unwrap())
+            .collect();
+        assert_eq!(2, filter_by_tags(&input_stats, &filters).len());
+        let senior = filter_by_tags(&input_stats, &["level:senior".parse().unwrap()]);
+        assert_eq!(
+            vec!["A"],
+            senior.iter().map(|s| s.company()).collect::<Vec<_>>()
+        );
+        assert!("level:".parse::<TagFilter>().is_err());
+
+        let found = search(&input_stats, "Recruiter SAID");
+        assert_eq!(1, found.len());
+        assert_eq!("B", found[0].company);
+    }
+
+    #[test]
+    fn test_tag_breakdown_and_metrics() {
+        let input_stats = input_stats_from_csv(CSV);
+        let breakdown = TagBreakdown::new(&input_stats, "hashtags".parse().unwrap());
+        let remote = breakdown
+            .groups
+            .iter()
+            .find(|g| g.label == "#remote")
+            .unwrap();
+        assert_eq!((2, 2, 1), (remote.applied, remote.responses, remote.offers));
+        assert!(breakdown.to_string().contains("|untagged|1|"));
+
+        let by_level = TagBreakdown::new(&input_stats, "level".parse().unwrap());
+        assert_eq!(3, by_level.groups.len());
+
+        let mut registry = MetricRegistry::empty();
+        register_tag_metrics(&input_stats, &mut registry);
+        assert_eq!(
+            vec![
+                "Tagged #contract",
+                "Tagged #onsite",
+                "Tagged #remote",
+                "Tagged level:mid",
+                "Tagged level:senior"
+            ],
+            registry.names()
+        );
+        let job_stats = JobStats::with_registry(input_stats, &registry);
+        assert_eq!(Some(2.0), job_stats.value("Tagged #remote"));
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::{significance, InputStat, JobStats};
+
+/// What rows with no owner are grouped under
+pub const UNKNOWN_OWNER: &str = "unknown";
+
+/// The owner a tracker file stands for, its name without the extension
+/// e.g. `alice` for `trackers/alice.csv`
+pub fn owner_from_path(path: &Path) -> String {
+    path.file_stem().map_or_else(
+        || String::from(UNKNOWN_OWNER),
+        |s| s.to_string_lossy().into_owned(),
+    )
+}
+
+/// Sets `owner` on every row that does not already name one in its `owner` column
+pub fn with_owner(input_stats: Vec<InputStat>, owner: &str) -> Vec<InputStat> {
+    input_stats
+        .into_iter()
+        .map(|mut stat| {
+            if stat.owner.is_none() {
+                stat.owner = Some(owner.to_string());
+            }
+            stat
+        })
+        .collect()
+}
+
+/// hearing back in any way, an interview invitation even if i declined it, a rejection or an
+/// offer
+pub(super) fn responded(stat: &InputStat) -> bool {
+    stat.first_interview.is_some() || stat.rejected_dt.is_some() || stat.offer_dt.is_some()
+}
+
+/// How often one person, or the whole team, heard back and got an offer
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemberRates {
+    pub owner: String,
+    pub applied: u32,
+    pub responses: u32,
+    pub offers: u32,
+}
+
+impl MemberRates {
+    fn new(owner: &str, input_stats: &[InputStat]) -> Self {
+        Self {
+            owner: owner.to_string(),
+            applied: input_stats.len() as u32,
+            responses: input_stats.iter().filter(|s| responded(s)).count() as u32,
+            offers: input_stats.iter().filter(|s| s.offer_dt.is_some()).count() as u32,
+        }
+    }
+
+    pub fn response_rate(&self) -> Option<f64> {
+        rate(self.responses, self.applied)
+    }
+
+    pub fn offer_rate(&self) -> Option<f64> {
+        rate(self.offers, self.applied)
+    }
+
+    /// the lower end of the 95% interval of the response rate, what the ranking is based on so a
+    /// rate over a handful of applications does not outrank one over many
+    pub fn response_rate_lower_bound(&self) -> Option<f64> {
+        lower_bound(self.responses, self.applied)
+    }
+
+    pub fn offer_rate_lower_bound(&self) -> Option<f64> {
+        lower_bound(self.offers, self.applied)
+    }
+
+    fn write_row(&self, f: &mut std::fmt::Formatter, rank: &str) -> std::fmt::Result {
+        writeln!(
+            f,
+            "|{}|{}|{}|{}|{}|",
+            rank,
+            self.owner,
+            self.applied,
+            significance::format_rate(self.responses, self.applied),
+            significance::format_rate(self.offers, self.applied)
+        )
+    }
+}
+
+fn lower_bound(successes: u32, trials: u32) -> Option<f64> {
+    significance::wilson_interval(successes, trials, significance::Z_95).map(|(low, _)| low)
+}
+
+fn rate(successes: u32, trials: u32) -> Option<f64> {
+    if trials == 0 {
+        None
+    } else {
+        Some(f64::from(successes) / f64::from(trials))
+    }
+}
+
+/// `JobStats` for each person of a team and for everyone together
+pub struct TeamStats {
+    /// best response rate first, ties broken by offer rate, both by the lower end of their
+    /// 95% interval
+    pub ranking: Vec<MemberRates>,
+    /// in order of owner
+    pub members: Vec<(String, JobStats)>,
+    pub total_rates: MemberRates,
+    pub total: JobStats,
+}
+
+impl TeamStats {
+    /// Groups the rows by their owner, rows with none are grouped under `UNKNOWN_OWNER`
+    pub fn new(input_stats: Vec<InputStat>) -> Self {
+        let mut by_owner: BTreeMap<String, Vec<InputStat>> = BTreeMap::new();
+        for stat in &input_stats {
+            let owner = stat.owner().unwrap_or(UNKNOWN_OWNER);
+            by_owner
+                .entry(owner.to_string())
+                .or_default()
+                .push(stat.clone());
+        }
+
+        let mut ranking: Vec<MemberRates> = by_owner
+            .iter()
+            .map(|(owner, stats)| MemberRates::new(owner, stats))
+            .collect();
+        ranking.sort_by(|a, b| {
+            let by_rate = |r: Option<f64>| r.unwrap_or(0.0);
+            by_rate(b.response_rate_lower_bound())
+                .total_cmp(&by_rate(a.response_rate_lower_bound()))
+                .then(
+                    by_rate(b.offer_rate_lower_bound())
+                        .total_cmp(&by_rate(a.offer_rate_lower_bound())),
+                )
+                .then_with(|| a.owner.cmp(&b.owner))
+        });
+
+        Self {
+            ranking,
+            members: by_owner
+                .into_iter()
+                .map(|(owner, stats)| (owner, JobStats::new(stats)))
+                .collect(),
+            total_rates: MemberRates::new("Team total", &input_stats),
+            total: JobStats::new(input_stats),
+        }
+    }
+}
+
+impl std::fmt::Display for TeamStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        writeln!(f, "|Rank|Owner|Jobs applied to|Response rate|Offer rate|")?;
+        writeln!(f, "|---|---|---|---|---|")?;
+        for (i, member) in self.ranking.iter().enumerate() {
+            member.write_row(f, &(i + 1).to_string())?;
+        }
+        self.total_rates.write_row(f, "")?;
+
+        writeln!(f)?;
+        writeln!(f, "## Team total")?;
+        writeln!(f)?;
+        write!(f, "{}", self.total)?;
+        for (owner, job_stats) in &self.members {
+            writeln!(f)?;
+            writeln!(f, "## {}", owner)?;
+            writeln!(f)?;
+            write!(f, "{}", job_stats)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::job_stat_helper::input_stats_from_csv;
+
+    #[test]
+    fn test_team_stats() {
+        let alice = input_stats_from_csv(
+            "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes
+A,Engineer,20220401,,20220405,20220420,100000,,,,
+B,Engineer,20220401,20220410,,,,,,,
+C,Engineer,20220401,,,,,,,,
+D,Engineer,20220401,,-1,,,,,,
+",
+        );
+        // a shared file that names the owner of each row
+        let shared = input_stats_from_csv(
+            "\
+company,position,applied_dt,rejected_dt,first_interview,offer_dt,offer_amt,accepted,referral,url,notes,owner
+E,Engineer,20220401,20220402,,,,,,,,bob
+F,Engineer,20220401,,,,,,,,,bob
+G,Engineer,20220401,,20220403,20220410,90000,,,,,carol
+H,Engineer,20220401,,,,,,,,,
+",
+        );
+        assert_eq!("alice", owner_from_path(Path::new("trackers/alice.csv")));
+
+        let mut input_stats = with_owner(alice, "alice");
+        input_stats.extend(with_owner(shared, "dave"));
+        let team = TeamStats::new(input_stats);
+
+        let owners: Vec<&str> = team.ranking.iter().map(|r| r.owner.as_str()).collect();
+        // carol heard back from her only application but alice's 3 of 4 says more
+        assert_eq!(vec!["alice", "carol", "bob", "dave"], owners);
+        assert_eq!(
+            MemberRates {
+                owner: String::from("alice"),
+                applied: 4,
+                responses: 3,
+                offers: 1,
+            },
+            team.ranking[0]
+        );
+        assert_eq!(Some(0.5), team.ranking[2].response_rate());
+        assert_eq!(8, team.total_rates.applied);
+        assert_eq!(Some(8.0), team.total.value("Jobs applied to"));
+        assert_eq!(Some(2.0), team.members[1].1.value("Jobs applied to"));
+
+        let rendered = team.to_string();
+        assert!(rendered.contains("|1|alice|4|3/4 = 75.00%"));
+        assert!(rendered.contains("|2|carol|1|1/1 = 100.00%"));
+        assert!(rendered.contains("||Team total|8|5/8 = 62.50%"));
+        assert!(rendered.contains("## bob"));
+    }
+}